@@ -1,60 +1,87 @@
 extern crate mine_sweeper;
-mod alpha_ai;
 
-use time::Duration;
-use std::{time::SystemTime};
-use mine_sweeper::{*, mine_sweeper::*};
-use std::time;
-use alpha_ai::*;
+use std::time::Duration;
+use mine_sweeper::{*, config::GameConfig, mine_sweeper::*, solver::{self, Solver}};
 
 fn main() {
-    let mut mine_sweeper: MineSweeper;
-    if let Ok((cols, rows, chance)) = get_args() {
-        mine_sweeper = MineSweeper::new(cols, rows, chance, ApperanceSettings::default());
-    }
-    else {
-        mine_sweeper = MineSweeper::default();
-    }
+    let config = match GameConfig::from_args() {
+        Ok(config) => config,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
+    };
+    let mut solver = match solver::from_args() {
+        Ok(solver) => solver,
+        Err(error) => {
+            println!("{}, choose one of {:?}", error, solver::SOLVER_NAMES);
+            return;
+        }
+    };
+    let mut mine_sweeper: MineSweeper = new_game(&config);
     let mut wins: usize = 0;
     let mut losses: usize = 0;
     let mut times: Vec<(Duration, usize)> = Vec::with_capacity(1000);
-    let mut alpha_ai: AlphaAI = AlphaAI::new(mine_sweeper.cols(), mine_sweeper.rows());
+    let mut steps: usize = 0;
+    solver.reset(&mine_sweeper.view());
 
     loop {
         match mine_sweeper.game_state {
             GameState::Running => {
                 //let time = SystemTime::now();
-                alpha_ai.update_ai(&mut mine_sweeper); 
-                //println!("step: {:?}, took {:?} us", alpha_ai.step, SystemTime::now().duration_since(time).unwrap().as_micros());
-                alpha_ai.step += 1;
+                // a failed move leaves the game as it was
+                let _ = solver::play_step(solver.as_mut(), &mut mine_sweeper);
+                //println!("step: {:?}, took {:?} us", steps, SystemTime::now().duration_since(time).unwrap().as_micros());
+                steps += 1;
 
             }
             GameState::Won => {
                 wins += 1; 
-                let time = SystemTime::now().duration_since(mine_sweeper.start_time).unwrap();
-                times.push((time , alpha_ai.step));
-                restart_no_ui(&mut alpha_ai, &mut mine_sweeper, &wins, &losses, &mut times);
+                let time = mine_sweeper.elapsed();
+                times.push((time , steps));
+                print_metrics(&mine_sweeper);
+                restart_no_ui(&config, solver.as_mut(), &mut mine_sweeper, &mut steps, &wins, &losses, &times);
                 println!("Time: {:?} ", time.as_micros());
             }
             GameState::Lost => {
                 losses += 1; 
-                let time = SystemTime::now().duration_since(mine_sweeper.start_time).unwrap();
-                restart_no_ui(&mut alpha_ai, &mut mine_sweeper, &wins, &losses, &mut times);
+                let time = mine_sweeper.elapsed();
+                print!("Lost seed: {:?} ", mine_sweeper.seed());
+                print_metrics(&mine_sweeper);
+                // keep the latest loss around so it can be shared and stepped through
+                if let Some(replay) = mine_sweeper.replay() {
+                    if let Err(error) = replay.save("last_loss.replay") {
+                        print!("Could not save replay: {} ", error);
+                    }
+                }
+                restart_no_ui(&config, solver.as_mut(), &mut mine_sweeper, &mut steps, &wins, &losses, &times);
                 println!("Time: {:?} ", time.as_micros());
             }
         }
     }
 }
 
-fn restart_no_ui(alpha_ai: &mut AlphaAI, mine_sweeper: &mut MineSweeper, wins: &usize, losses: &usize, times: &mut Vec<(Duration, usize)>) {
-    print!("Wins: {:?} Losses: {:?} : {:?} Steps: {:?} ", *wins, *losses, (*wins as f64/(*wins+*losses) as f64), alpha_ai.step);
-    print!("Time per step: {:?} ", if let Some(v) = average_time_per_step(times) {v.as_micros()} else {0});
+fn new_game(config: &GameConfig) -> MineSweeper {
+    // the config was validated when it was read, and with a seed every game is the same board
+    let mut mine_sweeper = MineSweeper::from_config(config).unwrap();
+    mine_sweeper.start_recording();
+    mine_sweeper
+}
 
-    if let Ok((cols, rows, chance)) = get_args() {
-        *mine_sweeper = MineSweeper::new(cols, rows, chance, ApperanceSettings::default());
+/// Difficulty of the board, and for won games how well the AI played it
+fn print_metrics(mine_sweeper: &MineSweeper) {
+    let metrics = mine_sweeper.board().metrics();
+    print!("3BV: {} Openings: {} Isolated: {} ", metrics.three_bv, metrics.openings, metrics.isolated_numbers);
+    if let (Some(efficiency), Some(per_second)) = (mine_sweeper.efficiency(), mine_sweeper.three_bv_per_second()) {
+        print!("Efficiency: {:.2} 3BV/s: {:.0} ", efficiency, per_second);
     }
-    else {
-        *mine_sweeper = MineSweeper::default();
-    }
-    *alpha_ai = AlphaAI::new(mine_sweeper.cols(), mine_sweeper.rows());
+}
+
+fn restart_no_ui(config: &GameConfig, solver: &mut dyn Solver, mine_sweeper: &mut MineSweeper, steps: &mut usize, wins: &usize, losses: &usize, times: &[(Duration, usize)]) {
+    print!("Wins: {:?} Losses: {:?} : {:?} Steps: {:?} ", *wins, *losses, (*wins as f64/(*wins+*losses) as f64), *steps);
+    print!("Time per step: {:?} ", if let Some(v) = average_time_per_step(times) {v.as_micros()} else {0});
+
+    *mine_sweeper = new_game(config);
+    *steps = 0;
+    solver.reset(&mine_sweeper.view());
 }
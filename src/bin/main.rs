@@ -5,12 +5,14 @@ extern crate piston;
 extern crate piston_window;
 
 use piston_window::*;
-use mine_sweeper::{*, mine_sweeper::*};
+use mine_sweeper::{*, config::GameConfig, mine_sweeper::*, frontend::*};
 use opengl_graphics::{GlGraphics, OpenGL};
-use piston::{Button, MouseButton, MouseCursorEvent, PressEvent, ResizeEvent, event_loop::{EventSettings, Events}};
+use piston::{Button, Key, MouseButton, MouseCursorEvent, PressEvent, ReleaseEvent, ResizeEvent, event_loop::{EventSettings, Events}};
 use piston::input::RenderEvent;
 use piston::window::WindowSettings;
 
+const SAVE_PATH: &str = "mine_sweeper.save";
+
 fn main() {
     // Create a window. the window need to be created before MineSweeper
     let mut window: PistonWindow = WindowSettings::new("Mine Sweeper", [100.0;2])
@@ -20,45 +22,135 @@ fn main() {
         .build()
         .unwrap();
 
-    let mut mine_sweeper: MineSweeper;
-    
-    if let Ok((cols, rows, chance)) = get_args() {
-        mine_sweeper = MineSweeper::new(cols, rows, chance, ApperanceSettings::default());
+    let load_path = get_load_path();
+    let mut mine_sweeper: MineSweeper = if let Some(path) = &load_path {
+        match MineSweeper::load(path) {
+            Ok(mine_sweeper) => mine_sweeper,
+            Err(error) => {
+                println!("Could not load {}: {}", path, error);
+                return;
+            }
+        }
     }
     else {
-        mine_sweeper = MineSweeper::default();
-    }
-    let window_size: [f64;2] = [mine_sweeper.apperance.square_size * mine_sweeper.cols() as f64, mine_sweeper.apperance.square_size * mine_sweeper.rows() as f64]; 
+        match GameConfig::from_args().and_then(|config| MineSweeper::from_config(&config)) {
+            Ok(mine_sweeper) => mine_sweeper,
+            Err(error) => {
+                println!("{}", error);
+                return;
+            }
+        }
+    };
+    // a loaded game is saved back to where it came from
+    let save_path = load_path.unwrap_or_else(|| SAVE_PATH.to_string());
+    let mut frontend = Frontend::default();
+    let window_size: [f64;2] = frontend.window_size(&mine_sweeper);
 
     //window.set_lazy(true);
     //window.set_max_fps(120);
     window.set_size(window_size);
-    
+
     let mut gl: GlGraphics = GlGraphics::new(OpenGL::V3_2);
     let mut cursor = [0.0, 0.0];
+    // buttons act when they are released, so pressing both at once chords, like the middle button, without revealing or flagging first
+    let mut left_down = false;
+    let mut right_down = false;
+    let mut chording = false;
+    let mut ctrl_down = false;
     let mut events = Events::new(EventSettings::new());
     while let Some(e) = events.next(&mut window) {
 
         e.mouse_cursor(|pos| cursor = pos);
-        e.resize(|args| mine_sweeper.scale = [args.window_size[0] / window_size[0], args.window_size[1] / window_size[1]]);
+        e.resize(|args| frontend.scale = [args.window_size[0] / window_size[0], args.window_size[1] / window_size[1]]);
+
+        if let Some(args) = e.render_args() { frontend.render(&mine_sweeper, &args, &mut gl); }
 
-        if let Some(args) = e.render_args() { mine_sweeper.render(&args, &mut gl); }
+        let click = match e.release_args() {
+            Some(Button::Mouse(MouseButton::Left)) => {
+                left_down = false;
+                button_released(&mut chording, right_down, Click::Reveal)
+            },
+            Some(Button::Mouse(MouseButton::Right)) => {
+                right_down = false;
+                button_released(&mut chording, left_down, Click::Flag)
+            },
+            Some(Button::Mouse(MouseButton::Middle)) => Some(Click::Chord),
+            Some(Button::Keyboard(Key::LCtrl)) | Some(Button::Keyboard(Key::RCtrl)) => {
+                ctrl_down = false;
+                None
+            },
+            _ => None
+        };
 
-        match mine_sweeper.game_state {
-            GameState::Running => {
-                if let Some(Button::Mouse(button)) = e.press_args() {
-                    match button {
-                        MouseButton::Left => {
-                            mine_sweeper.left_click([cursor[0] / mine_sweeper.scale[0], cursor[1] / mine_sweeper.scale[1]]);
-                        },
-                        MouseButton::Right => {
-                            mine_sweeper.right_click([cursor[0] / mine_sweeper.scale[0], cursor[1] / mine_sweeper.scale[1]]);
-                        },
-                        _ => ()
-                    }
+        // undo and redo also work after the game is over
+        match e.press_args() {
+            Some(Button::Mouse(MouseButton::Left)) => {
+                left_down = true;
+                chording |= right_down;
+            },
+            Some(Button::Mouse(MouseButton::Right)) => {
+                right_down = true;
+                chording |= left_down;
+            },
+            Some(Button::Keyboard(Key::LCtrl)) | Some(Button::Keyboard(Key::RCtrl)) => ctrl_down = true,
+            Some(Button::Keyboard(Key::Z)) if ctrl_down => {
+                if let Err(error) = mine_sweeper.undo() {
+                    println!("{}", error);
+                }
+            },
+            Some(Button::Keyboard(Key::Y)) if ctrl_down => {
+                if let Err(error) = mine_sweeper.redo() {
+                    println!("{}", error);
                 }
+            },
+            Some(Button::Keyboard(Key::S)) if ctrl_down => save(&mine_sweeper, &save_path),
+            Some(Button::Keyboard(Key::Q)) => {
+                mine_sweeper.question_marks = !mine_sweeper.question_marks;
+                println!("Question marks: {}", if mine_sweeper.question_marks {"on"} else {"off"});
+            },
+            _ => ()
+        }
+
+        if let (GameState::Running, Some(click)) = (mine_sweeper.game_state, click) {
+            let result = match click {
+                Click::Reveal => frontend.left_click(&mut mine_sweeper, cursor).map(|_| ()),
+                Click::Flag => frontend.right_click(&mut mine_sweeper, cursor).map(|_| ()),
+                Click::Chord => frontend.chord(&mut mine_sweeper, cursor).map(|_| ()),
+            };
+            if let Err(error) = result {
+                println!("{}", error);
             }
-            _ => (),
         }
     }
+
+    // closing the window keeps an unfinished game
+    if let GameState::Running = mine_sweeper.game_state {
+        save(&mine_sweeper, &save_path);
+    }
+}
+
+/// What a released mouse button does
+enum Click {
+    Reveal,
+    Flag,
+    Chord,
+}
+
+/// The click of a released button, or the chord when both were pressed: it happens on the first of the two releases, the second does nothing
+fn button_released(chording: &mut bool, other_down: bool, click: Click) -> Option<Click> {
+    if !*chording {
+        Some(click)
+    } else if other_down {
+        Some(Click::Chord)
+    } else {
+        *chording = false;
+        None
+    }
+}
+
+fn save(mine_sweeper: &MineSweeper, path: &str) {
+    match mine_sweeper.save(path) {
+        Ok(()) => println!("Saved to {}", path),
+        Err(error) => println!("Could not save to {}: {}", path, error),
+    }
 }
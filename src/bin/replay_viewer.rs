@@ -0,0 +1,180 @@
+extern crate graphics;
+extern crate opengl_graphics;
+extern crate piston;
+extern crate piston_window;
+extern crate mine_sweeper;
+
+use piston_window::*;
+use std::env::args;
+use std::time::Duration;
+use mine_sweeper::{frontend::*, replay::*};
+use opengl_graphics::{GlGraphics, OpenGL};
+use piston::{Button, Key, MouseButton, MouseCursorEvent, PressEvent, ReleaseEvent, ResizeEvent, event_loop::{EventSettings, Events}};
+use piston::input::{RenderEvent, UpdateEvent};
+use piston::window::WindowSettings;
+
+/// Height of the bar below the board that shows and sets how far the replay has played
+const SLIDER_HEIGHT: f64 = 20.0;
+const MAX_SPEED: f64 = 64.0;
+
+/// Space plays and pauses, Left and Right step, Up and Down change the speed,
+/// Home and End jump to the start and end, clicking or dragging the bar below the board jumps to a move,
+/// P prints the position and the mines of the current step
+fn main() {
+    let path = args().nth(1).unwrap_or_else(|| "last_loss.replay".to_string());
+    let replay = match Replay::load(&path) {
+        Ok(replay) => replay,
+        Err(error) => {
+            println!("Could not load {}: {}", path, error);
+            return;
+        }
+    };
+    let mut player = match ReplayPlayer::new(replay) {
+        Ok(player) => player,
+        Err(error) => {
+            println!("Could not replay {}: {}", path, error);
+            return;
+        }
+    };
+
+    // Create a window. the window need to be created before GlGraphics
+    let mut window: PistonWindow = WindowSettings::new("Mine Sweeper: Replay", [100.0;2])
+        .graphics_api(OpenGL::V3_2)
+        .exit_on_esc(true)
+        .build()
+        .unwrap();
+
+    let mut frontend = Frontend::default();
+    // the board is what we want to look at, also after the game is lost
+    frontend.apperance.cover_finished_games = false;
+    let board_size: [f64;2] = frontend.window_size(player.mine_sweeper());
+    let window_size: [f64;2] = [board_size[0], board_size[1] + SLIDER_HEIGHT];
+    window.set_size(window_size);
+
+    let mut gl: GlGraphics = GlGraphics::new(OpenGL::V3_2);
+    let mut cursor = [0.0, 0.0];
+    let mut dragging = false;
+    let mut playing = false;
+    let mut speed: f64 = 1.0;
+    // how far into the recorded game the playback is
+    let mut played = Duration::from_millis(0);
+    let mut last_position = usize::MAX;
+    let mut events = Events::new(EventSettings::new());
+    while let Some(e) = events.next(&mut window) {
+
+        e.resize(|args| frontend.scale = [args.window_size[0] / window_size[0], args.window_size[1] / window_size[1]]);
+
+        if let Some(args) = e.render_args() {
+            frontend.render(player.mine_sweeper(), &args, &mut gl);
+            render_slider(&frontend, &player, board_size, &args, &mut gl);
+        }
+
+        if let Some(pos) = e.mouse_cursor_args() {
+            cursor = pos;
+            if dragging {
+                let position = slider_position(&frontend, player.len(), board_size, cursor);
+                seek(&mut player, position, &mut played);
+            }
+        }
+
+        if let Some(Button::Mouse(MouseButton::Left)) = e.release_args() {
+            dragging = false;
+        }
+
+        match e.press_args() {
+            Some(Button::Keyboard(Key::Space)) => {
+                // playing from the end starts over
+                if !playing && player.position() == player.len() {
+                    seek(&mut player, 0, &mut played);
+                }
+                playing = !playing;
+            },
+            Some(Button::Keyboard(Key::Right)) => {
+                playing = false;
+                let position = player.position() + 1;
+                seek(&mut player, position, &mut played);
+            },
+            Some(Button::Keyboard(Key::Left)) => {
+                playing = false;
+                let position = player.position().saturating_sub(1);
+                seek(&mut player, position, &mut played);
+            },
+            Some(Button::Keyboard(Key::Up)) => speed = (speed * 2.0).min(MAX_SPEED),
+            Some(Button::Keyboard(Key::Down)) => speed = (speed / 2.0).max(1.0 / MAX_SPEED),
+            Some(Button::Keyboard(Key::Home)) => seek(&mut player, 0, &mut played),
+            Some(Button::Keyboard(Key::End)) => {
+                let position = player.len();
+                seek(&mut player, position, &mut played);
+            },
+            Some(Button::Keyboard(Key::P)) => {
+                // ready to paste into a test with `MineSweeper::from_position`
+                let mine_sweeper = player.mine_sweeper();
+                println!("Position:\n{}Truth:\n{}", mine_sweeper.to_position_string(), mine_sweeper.board());
+            },
+            Some(Button::Mouse(MouseButton::Left)) if cursor[1] / frontend.scale[1] >= board_size[1] => {
+                dragging = true;
+                let position = slider_position(&frontend, player.len(), board_size, cursor);
+                seek(&mut player, position, &mut played);
+            },
+            _ => ()
+        }
+
+        if let Some(args) = e.update_args() {
+            if playing {
+                played += Duration::from_secs_f64(args.dt * speed);
+                while player.next_time().is_some_and(|time| time <= played) {
+                    if let Some(Err(error)) = player.step_forward() {
+                        println!("{}", error);
+                    }
+                }
+                if player.position() == player.len() {
+                    playing = false;
+                }
+            }
+
+            if player.position() != last_position {
+                last_position = player.position();
+                print_step(&player);
+            }
+            window.set_title(format!("Mine Sweeper: Replay {}/{} x{} {}", player.position(), player.len(), speed, if playing {"playing"} else {"paused"}));
+        }
+    }
+}
+
+/// Jumps to `position` and moves the playback time to when that step was made
+fn seek(player: &mut ReplayPlayer, position: usize, played: &mut Duration) {
+    if let Err(error) = player.seek(position) {
+        println!("{}", error);
+    }
+    *played = match player.position() {
+        0 => Duration::from_millis(0),
+        position => player.replay().actions[position - 1].0,
+    };
+}
+
+/// The step under `cursor` on the slider, `cursor` is in window coordinates
+fn slider_position(frontend: &Frontend, len: usize, board_size: [f64;2], cursor: [f64;2]) -> usize {
+    let fraction = (cursor[0] / frontend.scale[0] / board_size[0]).clamp(0.0, 1.0);
+    (fraction * len as f64).round() as usize
+}
+
+fn print_step(player: &ReplayPlayer) {
+    let position = player.position();
+    match position.checked_sub(1).map(|i| player.replay().actions[i]) {
+        Some((time, action)) => println!("Step {}/{} at {:?}: {:?}, {:?}", position, player.len(), time, action, player.mine_sweeper().game_state),
+        None => println!("Step 0/{}", player.len()),
+    }
+}
+
+fn render_slider(frontend: &Frontend, player: &ReplayPlayer, board_size: [f64;2], args: &RenderArgs, gl: &mut GlGraphics) {
+    use graphics::*;
+
+    let fraction = if player.is_empty() {1.0} else {player.position() as f64 / player.len() as f64};
+    gl.draw(args.viewport(), |c, gl| {
+        let windows_scaling: [f64;2] = [c.get_view_size()[0] / c.viewport.unwrap().draw_size[0] as f64, c.get_view_size()[1] / c.viewport.unwrap().draw_size[1] as f64];
+        let transform = c.transform.scale(windows_scaling[0], windows_scaling[1]).scale(frontend.scale[0], frontend.scale[1]);
+        let radius = frontend.apperance.line_radius;
+        rectangle(frontend.apperance.background_color, [0.0, board_size[1], board_size[0], SLIDER_HEIGHT], transform, gl);
+        rectangle(frontend.apperance.square_color, [radius, board_size[1] + radius, (board_size[0] - radius * 2.0) * fraction, SLIDER_HEIGHT - radius * 2.0], transform, gl);
+    });
+}
@@ -0,0 +1,547 @@
+use std::char::from_digit;
+use std::fmt;
+use rand::prelude::*;
+use rand::seq::index;
+use rand_chacha::ChaCha8Rng;
+use crate::deduction::Deduction;
+use crate::error::MineSweeperError;
+
+/// Rows of cells that are either a mine (`None`) or the number of adjacent mines
+pub type MineField = Vec<Vec<Option<u8>>>;
+
+pub const NEIGHBOURS: [[i8;2];8] = [[-1, -1], [-1, 0], [-1, 1], [0, -1], [0, 1], [1, -1], [1, 0], [1, 1] ];
+
+/// How mines are placed when generating a board
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Density {
+    /// Every cell is a mine with this chance, so the number of mines varies between boards
+    Concentration(f64),
+    /// Exactly this many mines at uniformly sampled positions
+    Count(usize),
+}
+
+/// What the first reveal of a game is guaranteed to hit
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum FirstClick {
+    /// No guarantee, the first reveal can lose the game
+    Unsafe,
+    /// The first revealed cell is never a mine
+    #[default]
+    Safe,
+    /// The first revealed cell is a zero, so it always opens up an area
+    Opening,
+    /// The whole board is regenerated around the first reveal so it can be solved without guessing,
+    /// see `generate_no_guess_grid`
+    NoGuess,
+}
+
+/// The hidden truth of a game, where each cell is either a mine (`None`) or the number of adjacent mines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Board {
+    pub(crate) mine_field: MineField,
+    mine_count: usize,
+    seed: Option<u64>,
+    /// Set when `Board::generate` recreates this board from `seed`
+    density: Option<Density>,
+}
+
+/// Writes the mines and numbers like `print`, `Board::parse` reads it back
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_layout(f, &self.mine_field)
+    }
+}
+
+impl Board {
+    /// Generates a random board where each cell has `concentration` chance of being a mine
+    pub fn new(cols: usize, rows: usize, concentration: f64) -> Self {
+        Board::from_rng(cols, rows, concentration, &mut rand::thread_rng())
+    }
+
+    /// Generates the same board for the same seed, dimensions and concentration on every run and platform
+    pub fn with_seed(cols: usize, rows: usize, concentration: f64, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut mine_count: usize = 0;
+        let mine_field = generate_random_grid(cols, rows, concentration, &mut rng, &mut mine_count);
+        Board { mine_field, mine_count, seed: Some(seed), density: Some(Density::Concentration(concentration)) }
+    }
+
+    /// Generates a board with either mode of `Density`, fails if there are more mines than cells or the board is empty
+    pub fn generate(cols: usize, rows: usize, density: Density, seed: u64) -> Result<Self, MineSweeperError> {
+        if cols == 0 || rows == 0 {
+            return Err(MineSweeperError::InvalidConfig("a board needs at least one column and row"));
+        }
+        match density {
+            Density::Concentration(concentration) if !(0.0..=1.0).contains(&concentration) => Err(MineSweeperError::InvalidConfig("concentration must be between 0 and 1")),
+            Density::Concentration(concentration) => Ok(Board::with_seed(cols, rows, concentration, seed)),
+            Density::Count(mine_count) => {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                let mine_field = generate_exact_grid(cols, rows, mine_count, &mut rng)?;
+                Ok(Board { mine_field, mine_count, seed: Some(seed), density: Some(density) })
+            }
+        }
+    }
+
+    /// Draws a seed from `rng` so that the board can be recreated with `Board::with_seed`
+    pub fn from_rng<R: RngCore + ?Sized>(cols: usize, rows: usize, concentration: f64, rng: &mut R) -> Self {
+        Board::with_seed(cols, rows, concentration, rng.next_u64())
+    }
+
+    /// Uses the mines of `mine_field` and recounts every number, fails if the rows are empty or of different lengths
+    pub fn from_mine_field(mut mine_field: MineField) -> Result<Self, MineSweeperError> {
+        if mine_field.is_empty() || mine_field[0].is_empty() {
+            return Err(MineSweeperError::InvalidConfig("a board needs at least one column and row"));
+        }
+        if mine_field.iter().any(|row| row.len() != mine_field[0].len()) {
+            return Err(MineSweeperError::InvalidConfig("all rows of a board must be equally long"));
+        }
+        count_adjacent_mines(&mut mine_field);
+        let mine_count = mine_field.iter().flatten().filter(|cell| cell.is_none()).count();
+        Ok(Board { mine_field, mine_count, seed: None, density: None })
+    }
+
+    /// Reads a board written by `print`, or by hand for tests and puzzles.
+    /// Mines are `X` or `*`, safe cells are digits or `.` when their number is left out, spaces between cells are optional.
+    /// The flags `F` (on a mine) and `f` (on a safe cell) of `MineSweeper::from_text` are accepted as well.
+    /// Fails if a written number does not match the mines around it
+    pub fn parse(text: &str) -> Result<Self, MineSweeperError> {
+        parse_cells(text).map(|(board, _)| board)
+    }
+
+    /// 3BV, openings and isolated numbers of the current layout, see `board_metrics`
+    pub fn metrics(&self) -> BoardMetrics {
+        board_metrics(&self.mine_field)
+    }
+
+    /// Remembers where a layout loaded with `from_mine_field` was originally generated from
+    pub(crate) fn set_origin(&mut self, seed: Option<u64>, density: Option<Density>) {
+        self.seed = seed;
+        self.density = density;
+    }
+
+    /// The seed the board was generated from
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The density to pass to `Board::generate` together with `seed` to get this board again,
+    /// `None` if the board was not generated that way
+    pub fn density(&self) -> Option<Density> {
+        self.density
+    }
+
+    pub fn cols(&self) -> usize {
+        self.mine_field[0].len()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.mine_field.len()
+    }
+
+    pub fn mine_count(&self) -> usize {
+        self.mine_count
+    }
+
+    /// Returns `None` if the cell is a mine, otherwise the number of adjacent mines
+    pub fn get(&self, position: [usize;2]) -> Option<u8> {
+        self.mine_field[position[1]][position[0]]
+    }
+
+    pub fn is_mine(&self, position: [usize;2]) -> bool {
+        self.get(position).is_none()
+    }
+
+    pub fn neighbours(&self, position: [usize;2]) -> impl Iterator<Item = [usize;2]> {
+        neighbours(position, self.cols(), self.rows())
+    }
+
+    /// Moves mines away from `position` so that it satisfies `first_click`, keeping the mine count.
+    /// Mines are moved to random free cells, chosen from the seed when there is one so seeded games stay reproducible.
+    /// On boards too dense for an opening the cell is only made safe, a board without free cells is left as is.
+    pub fn relocate_mines(&mut self, position: [usize;2], first_click: FirstClick) {
+        let mut excluded: Vec<[usize;2]> = vec![position];
+        match first_click {
+            FirstClick::Unsafe => return,
+            FirstClick::Safe => {},
+            FirstClick::Opening => excluded.extend(self.neighbours(position)),
+            FirstClick::NoGuess => {
+                let mut rng = self.relocation_rng(2);
+                if let Ok((mine_field, _)) = generate_no_guess_grid(self.cols(), self.rows(), self.mine_count, position, NO_GUESS_ATTEMPTS, &mut rng) {
+                    self.mine_field = mine_field;
+                } else {
+                    self.relocate_mines(position, FirstClick::Opening);
+                }
+                return;
+            }
+        }
+        let mut free: Vec<[usize;2]> = Vec::new();
+        for y in 0..self.rows() {
+            for x in 0..self.cols() {
+                if self.mine_field[y][x].is_some() && !excluded.contains(&[x, y]) {
+                    free.push([x, y]);
+                }
+            }
+        }
+        let mut moved: Vec<[usize;2]> = excluded.iter().copied().filter(|cell| self.is_mine(*cell)).collect();
+        if moved.len() > free.len() {
+            // not enough room for an opening, settle for a safe first click
+            moved.retain(|cell| *cell == position);
+            if moved.len() > free.len() {
+                return;
+            }
+        }
+
+        let mut rng = self.relocation_rng(1);
+        for (from, i) in moved.iter().zip(index::sample(&mut rng, free.len(), moved.len()).into_iter()) {
+            let to = free[i];
+            self.mine_field[from[1]][from[0]] = Some(0);
+            self.mine_field[to[1]][to[0]] = None;
+        }
+        count_adjacent_mines(&mut self.mine_field);
+    }
+
+    /// Derived from the seed, on its own `stream` so it does not repeat the numbers that placed the mines
+    fn relocation_rng(&self, stream: u64) -> Box<dyn RngCore> {
+        match self.seed {
+            Some(seed) => {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                rng.set_stream(stream);
+                Box::new(rng)
+            }
+            None => Box::new(rand::thread_rng()),
+        }
+    }
+
+    /// Generates a board that can be solved without guessing when `start` is revealed first.
+    /// A concentration is turned into the nearest mine count. Fails like `generate_no_guess_grid`.
+    pub fn no_guess(cols: usize, rows: usize, density: Density, start: [usize;2], seed: u64, max_attempts: usize) -> Result<(Self, NoGuessReport), MineSweeperError> {
+        let mine_count = match density {
+            Density::Concentration(concentration) => (concentration * (cols * rows) as f64).round() as usize,
+            Density::Count(mine_count) => mine_count,
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let (mine_field, report) = generate_no_guess_grid(cols, rows, mine_count, start, max_attempts, &mut rng)?;
+        Ok((Board { mine_field, mine_count, seed: Some(seed), density: None }, report))
+    }
+}
+
+/// Iterates over the in-bounds neighbours of `position`
+pub fn neighbours(position: [usize;2], cols: usize, rows: usize) -> impl Iterator<Item = [usize;2]> {
+    NEIGHBOURS.iter().filter_map(move |neighbour| {
+        let x = position[0] as isize + neighbour[0] as isize;
+        let y = position[1] as isize + neighbour[1] as isize;
+        if x < 0 || x >= cols as isize || y < 0 || y >= rows as isize {
+            return None;
+        }
+        Some([x as usize, y as usize])
+    })
+}
+
+fn rand_func<R: Rng + ?Sized>(rng: &mut R, chance: f64) -> bool {
+    let y = rng.gen_range(0.0, 1.0);
+    y <= chance
+}
+
+pub fn generate_random_grid<R: Rng + ?Sized>(cols: usize, rows: usize, mine_concentration: f64, rng: &mut R, mine_count_output: &mut usize) -> Vec<Vec<Option<u8>>> {
+    let mut squares: Vec<Vec<Option<u8>>>  = vec![vec![None;cols];rows];
+    for row in squares.iter_mut() {
+        for sq in row.iter_mut() {
+            *sq = if rand_func(rng, mine_concentration) {*mine_count_output += 1; None} else {Some(0)}
+        }
+    }
+    // count mines in neighbouring squares
+    count_adjacent_mines(&mut squares);
+
+    squares
+}
+
+/// Places exactly `mine_count` mines, fails if they do not fit on the board
+pub fn generate_exact_grid<R: Rng + ?Sized>(cols: usize, rows: usize, mine_count: usize, rng: &mut R) -> Result<Vec<Vec<Option<u8>>>, MineSweeperError> {
+    if mine_count > cols * rows {
+        return Err(MineSweeperError::InvalidConfig("more mines than cells"));
+    }
+    let mut squares: Vec<Vec<Option<u8>>>  = vec![vec![Some(0);cols];rows];
+    for i in index::sample(rng, cols * rows, mine_count).into_iter() {
+        squares[i / cols][i % cols] = None;
+    }
+    count_adjacent_mines(&mut squares);
+
+    Ok(squares)
+}
+
+/// How many boards `generate_no_guess_grid` may check before it gives up, for the first click of a `NoGuess` game
+pub const NO_GUESS_ATTEMPTS: usize = 10_000;
+
+/// Mutations tried on one board before `generate_no_guess_grid` gives up on it and starts over
+const MUTATIONS_PER_BOARD: usize = 50;
+
+/// How much work `generate_no_guess_grid` needed to find its board
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NoGuessReport {
+    /// Boards checked by the solver, including the mutated ones
+    pub attempts: usize,
+    /// Attempts that moved a mine on the previous board instead of starting over
+    pub mutations: usize,
+}
+
+/// Generates a board with exactly `mine_count` mines that can be solved by deduction alone after revealing `start`,
+/// which is always a zero. Whenever the solver gets stuck a mine it was stuck on is moved somewhere it has not reached,
+/// and a fresh board is generated when that is not possible or does not help, until `max_attempts` boards have been checked.
+/// Fails if the board is empty or `start` is not on it.
+pub fn generate_no_guess_grid<R: Rng + ?Sized>(cols: usize, rows: usize, mine_count: usize, start: [usize;2], max_attempts: usize, rng: &mut R) -> Result<(MineField, NoGuessReport), MineSweeperError> {
+    if cols == 0 || rows == 0 {
+        return Err(MineSweeperError::InvalidConfig("a board needs at least one column and row"));
+    }
+    if start[0] >= cols || start[1] >= rows {
+        return Err(MineSweeperError::OutOfBounds(start));
+    }
+    let mut opening: Vec<[usize;2]> = neighbours(start, cols, rows).collect();
+    opening.push(start);
+    let candidates: Vec<[usize;2]> = (0..rows)
+        .flat_map(|y| (0..cols).map(move |x| [x, y]))
+        .filter(|cell| !opening.contains(cell))
+        .collect();
+    if mine_count > candidates.len() {
+        return Err(MineSweeperError::InvalidConfig("too many mines to open up the first reveal"));
+    }
+    let fresh = |rng: &mut R| {
+        let mut squares: MineField = vec![vec![Some(0);cols];rows];
+        for i in index::sample(rng, candidates.len(), mine_count).into_iter() {
+            let cell = candidates[i];
+            squares[cell[1]][cell[0]] = None;
+        }
+        count_adjacent_mines(&mut squares);
+        squares
+    };
+
+    let mut report = NoGuessReport { attempts: 0, mutations: 0 };
+    let mut squares = fresh(rng);
+    let mut mutations: usize = 0;
+    while report.attempts < max_attempts {
+        report.attempts += 1;
+        let deduction = Deduction::run(&squares, start);
+        if deduction.is_solved() {
+            return Ok((squares, report));
+        }
+
+        let stuck: Vec<[usize;2]> = deduction.frontier().into_iter().filter(|cell| squares[cell[1]][cell[0]].is_none()).collect();
+        let mut targets: Vec<[usize;2]> = deduction.interior();
+        if targets.is_empty() {
+            targets = deduction.frontier();
+        }
+        targets.retain(|cell| squares[cell[1]][cell[0]].is_some() && !opening.contains(cell));
+        if stuck.is_empty() || targets.is_empty() || mutations >= MUTATIONS_PER_BOARD {
+            squares = fresh(rng);
+            mutations = 0;
+            continue;
+        }
+        let from = stuck[rng.gen_range(0, stuck.len())];
+        let to = targets[rng.gen_range(0, targets.len())];
+        squares[from[1]][from[0]] = Some(0);
+        squares[to[1]][to[0]] = None;
+        count_adjacent_mines(&mut squares);
+        mutations += 1;
+        report.mutations += 1;
+    }
+    Err(MineSweeperError::InvalidConfig("no board without guessing found within max_attempts"))
+}
+
+/// Standard measures of how much work a board is, independent of how it was played
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BoardMetrics {
+    /// The least clicks needed to solve the board without flags: one per opening and one per isolated number
+    pub three_bv: usize,
+    /// Connected areas of zeros, each opens with a single click
+    pub openings: usize,
+    /// Numbers that do not touch a zero, so they have to be clicked one by one
+    pub isolated_numbers: usize,
+}
+
+/// Counts the openings and isolated numbers of `mine_field` for its 3BV
+pub fn board_metrics(mine_field: &[Vec<Option<u8>>]) -> BoardMetrics {
+    let rows = mine_field.len();
+    let cols = mine_field.first().map_or(0, |row| row.len());
+    let mut in_opening = vec![vec![false; cols]; rows];
+    let mut openings = 0;
+    for y in 0..rows {
+        for x in 0..cols {
+            if mine_field[y][x] != Some(0) || in_opening[y][x] {
+                continue;
+            }
+            // flood the zeros of this opening, the numbers around it are opened with it
+            openings += 1;
+            in_opening[y][x] = true;
+            let mut queue = vec![[x, y]];
+            while let Some(cell) = queue.pop() {
+                for neighbour in neighbours(cell, cols, rows) {
+                    if !in_opening[neighbour[1]][neighbour[0]] {
+                        in_opening[neighbour[1]][neighbour[0]] = true;
+                        if mine_field[neighbour[1]][neighbour[0]] == Some(0) {
+                            queue.push(neighbour);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let isolated_numbers = (0..rows)
+        .flat_map(|y| (0..cols).map(move |x| [x, y]))
+        .filter(|cell| mine_field[cell[1]][cell[0]].is_some() && !in_opening[cell[1]][cell[0]])
+        .count();
+    BoardMetrics { three_bv: openings + isolated_numbers, openings, isolated_numbers }
+}
+
+/// Writes the rows like `print` does
+pub(crate) fn write_layout(f: &mut fmt::Formatter<'_>, mine_field: &[Vec<Option<u8>>]) -> fmt::Result {
+    for row in mine_field.iter() {
+        let row: Vec<String> = row.iter().map(|cell| match cell {None => "X".to_string(), Some(value) => value.to_string()}).collect();
+        writeln!(f, "{}", row.join(" "))?;
+    }
+    Ok(())
+}
+
+/// The board of `Board::parse` and the character of every cell, so shown states can be read from the same text
+pub(crate) fn parse_cells(text: &str) -> Result<(Board, Vec<Vec<char>>), MineSweeperError> {
+    let lines: Vec<(usize, Vec<char>)> = text.lines().enumerate()
+        .map(|(i, line)| (i + 1, line.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>()))
+        .filter(|(_, cells)| !cells.is_empty())
+        .collect();
+    let mut mine_field: MineField = Vec::with_capacity(lines.len());
+    for (line, cells) in lines.iter() {
+        let row = cells.iter().map(|cell| match cell {
+            'X' | '*' | 'F' => Ok(None),
+            '.' | 'f' | '0'..='8' => Ok(Some(0)),
+            other => Err(MineSweeperError::Parse { line: *line, reason: format!("unknown cell {}", other) }),
+        }).collect::<Result<Vec<Option<u8>>, _>>()?;
+        if !mine_field.is_empty() && row.len() != mine_field[0].len() {
+            return Err(MineSweeperError::Parse { line: *line, reason: format!("expected {} cells", mine_field[0].len()) });
+        }
+        mine_field.push(row);
+    }
+    let board = Board::from_mine_field(mine_field)?;
+    for (y, (line, cells)) in lines.iter().enumerate() {
+        for (x, cell) in cells.iter().enumerate() {
+            if let Some(value) = cell.to_digit(10) {
+                let actual = board.get([x, y]).unwrap_or_default();
+                if value != actual as u32 {
+                    return Err(MineSweeperError::Parse { line: *line, reason: format!("[{}, {}] is written as {} but has {} mines around it", x, y, value, actual) });
+                }
+            }
+        }
+    }
+    Ok((board, lines.into_iter().map(|(_, cells)| cells).collect()))
+}
+
+fn count_adjacent_mines(squares: &mut [Vec<Option<u8>>]) {
+    let rows = squares.len();
+    for row in 0..rows {
+        let cols = squares[row].len();
+        for col in 0..cols {
+            if squares[row][col].is_some() {
+                let count = neighbours([col, row], cols, rows).filter(|n| squares[n[1]][n[0]].is_none()).count();
+                squares[row][col] = Some(count as u8);
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub fn print(mine_field: &[Vec<Option<u8>>]) {
+    let mut output: String = "\n".to_string();
+    for row in mine_field.iter() {
+        for (i, sq) in row.iter().enumerate() {
+            output.push(match sq {None => 'X', Some(v) => from_digit((*v).into(), 10).unwrap()});  // add this square´s character to output
+            if (i + 1) % row.len() == 0 { // if at the end of row, add new-line character
+                output.push('\n');
+            }
+            else {  // else add space
+                output.push(' ');
+            }
+        }
+    }
+    println!("{}", output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mine_sweeper::MineSweeper;
+
+    #[test]
+    fn seeds_give_the_same_layout_everywhere() {
+        // changing the rng or how cells are sampled breaks every shared seed, this catches it
+        assert_eq!(Board::with_seed(6, 4, 0.2, 42).to_string(), "\
+0 0 0 0 1 X
+0 0 1 1 3 2
+1 2 3 X 3 X
+1 X X 2 3 X
+");
+        assert_eq!(Board::generate(6, 4, Density::Count(5), 42).unwrap().to_string(), "\
+0 0 1 X X 1
+0 1 2 3 3 2
+0 1 X 2 2 X
+0 1 1 2 X 2
+");
+    }
+
+    #[test]
+    fn generate_rejects_more_mines_than_cells() {
+        assert_eq!(Board::generate(3, 3, Density::Count(10), 0).unwrap_err(), MineSweeperError::InvalidConfig("more mines than cells"));
+        assert!(matches!(MineSweeper::generate(3, 3, Density::Count(10), 0), Err(MineSweeperError::InvalidConfig(_))));
+        // every cell a mine still fits
+        assert_eq!(Board::generate(3, 3, Density::Count(9), 0).unwrap().mine_count(), 9);
+    }
+
+    /// Relocates with `first_click` and checks the mines were only moved, never added or removed
+    fn relocated(cols: usize, rows: usize, mine_count: usize, seed: u64, position: [usize;2], first_click: FirstClick) -> Board {
+        let mut board = Board::generate(cols, rows, Density::Count(mine_count), seed).unwrap();
+        board.relocate_mines(position, first_click);
+        assert_eq!(board.mine_field.iter().flatten().filter(|cell| cell.is_none()).count(), mine_count);
+        assert_eq!(board.mine_field, Board::from_mine_field(board.mine_field.clone()).unwrap().mine_field);
+        board
+    }
+
+    #[test]
+    fn relocate_mines_for_the_first_click() {
+        for seed in 0..50 {
+            let position = [seed as usize % 9, seed as usize / 9 % 9];
+            let safe = relocated(9, 9, 10, seed, position, FirstClick::Safe);
+            assert!(!safe.is_mine(position));
+            assert_eq!(safe, relocated(9, 9, 10, seed, position, FirstClick::Safe));
+
+            let opening = relocated(9, 9, 10, seed, position, FirstClick::Opening);
+            assert_eq!(opening.get(position), Some(0));
+            assert_eq!(opening, relocated(9, 9, 10, seed, position, FirstClick::Opening));
+
+            // 12 mines in 16 cells leave no room for an opening in the middle, the click is still safe
+            let dense = relocated(4, 4, 12, seed, [1, 1], FirstClick::Opening);
+            assert!(!dense.is_mine([1, 1]));
+            assert_eq!(dense, relocated(4, 4, 12, seed, [1, 1], FirstClick::Opening));
+        }
+        // without a free cell nothing can move
+        assert_eq!(relocated(3, 3, 9, 0, [1, 1], FirstClick::Safe), Board::generate(3, 3, Density::Count(9), 0).unwrap());
+    }
+
+    #[test]
+    fn no_guess_boards_can_be_solved_from_the_start() {
+        for seed in 0..10 {
+            let start = [seed as usize % 16, 8];
+            let (board, report) = Board::no_guess(16, 16, Density::Count(40), start, seed, NO_GUESS_ATTEMPTS).unwrap();
+            assert_eq!(board.get(start), Some(0));
+            assert_eq!((board.mine_count(), board.mine_field.iter().flatten().filter(|cell| cell.is_none()).count()), (40, 40));
+            assert!(Deduction::run(&board.mine_field, start).is_solved());
+            assert!(report.attempts >= 1 && report.attempts <= NO_GUESS_ATTEMPTS);
+            assert!(report.mutations < report.attempts);
+            assert_eq!(Board::no_guess(16, 16, Density::Count(40), start, seed, NO_GUESS_ATTEMPTS).unwrap(), (board, report));
+        }
+        // a board that never gets past its first attempt reports exactly that
+        let (_, report) = Board::no_guess(9, 9, Density::Count(0), [4, 4], 0, 1).unwrap();
+        assert_eq!(report, NoGuessReport { attempts: 1, mutations: 0 });
+    }
+
+    #[test]
+    fn no_guess_rejects_boards_it_can_not_start_on() {
+        assert_eq!(Board::no_guess(9, 9, Density::Count(10), [20, 20], 0, NO_GUESS_ATTEMPTS).unwrap_err(), MineSweeperError::OutOfBounds([20, 20]));
+        assert!(matches!(Board::no_guess(0, 9, Density::Count(0), [0, 0], 0, NO_GUESS_ATTEMPTS), Err(MineSweeperError::InvalidConfig(_))));
+    }
+}
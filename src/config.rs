@@ -0,0 +1,173 @@
+use crate::board::{Density, FirstClick};
+use crate::error::MineSweeperError;
+use crate::text::parse_first_click;
+
+/// The standard board sizes, or any other
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Difficulty {
+    /// 9*9 with 10 mines
+    Beginner,
+    /// 16*16 with 40 mines
+    Intermediate,
+    /// 30*16 with 99 mines
+    Expert,
+    Custom { cols: usize, rows: usize, density: Density },
+}
+
+impl Difficulty {
+    /// `beginner`, `intermediate` or `expert`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "beginner" => Some(Difficulty::Beginner),
+            "intermediate" => Some(Difficulty::Intermediate),
+            "expert" => Some(Difficulty::Expert),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> (usize, usize, Density) {
+        match self {
+            Difficulty::Beginner => (9, 9, Density::Count(10)),
+            Difficulty::Intermediate => (16, 16, Density::Count(40)),
+            Difficulty::Expert => (30, 16, Density::Count(99)),
+            Difficulty::Custom { cols, rows, density } => (cols, rows, density),
+        }
+    }
+}
+
+/// Everything needed to start a game, see `MineSweeper::from_config`.
+/// The size can only be set through `new`, so a config always describes a board that can be generated
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GameConfig {
+    difficulty: Difficulty,
+    /// A random seed is drawn for every game when `None`
+    pub seed: Option<u64>,
+    pub first_click: FirstClick,
+    pub win_on_all_flagged: bool,
+    pub question_marks: bool,
+}
+
+impl Default for GameConfig {
+    /// Intermediate with a random seed
+    fn default() -> Self {
+        GameConfig::new(Difficulty::Intermediate).unwrap()
+    }
+}
+
+impl GameConfig {
+    /// Fails if the board would be empty, the concentration is not between 0 and 1 or there are more mines than cells
+    pub fn new(difficulty: Difficulty) -> Result<Self, MineSweeperError> {
+        let (cols, rows, density) = difficulty.size();
+        if cols == 0 || rows == 0 {
+            return Err(MineSweeperError::InvalidConfig("a board needs at least one column and row"));
+        }
+        match density {
+            Density::Concentration(concentration) if !(0.0..=1.0).contains(&concentration) => return Err(MineSweeperError::InvalidConfig("concentration must be between 0 and 1")),
+            Density::Count(mine_count) if mine_count > cols * rows => return Err(MineSweeperError::InvalidConfig("more mines than cells")),
+            _ => {},
+        }
+        Ok(GameConfig {
+            difficulty,
+            seed: None,
+            first_click: FirstClick::default(),
+            win_on_all_flagged: false,
+            question_marks: false,
+        })
+    }
+
+    /// Reads the arguments given to the program, see `parse_args`
+    pub fn from_args() -> Result<Self, MineSweeperError> {
+        GameConfig::parse_args(std::env::args().skip(1))
+    }
+
+    /// Reads a preset name or `cols rows density`, followed by any of
+    /// `--seed <u64>`, `--first-click <unsafe|safe|opening|no_guess>`, `--win-on-all-flagged` and `--question-marks`.
+    /// A whole number density is a mine count, anything else a concentration. `--load <file>` and `--solver <name>` are skipped.
+    /// Without a size the game is intermediate
+    pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, MineSweeperError> {
+        let mut args = args.into_iter().peekable();
+        let first = args.peek().cloned();
+        let difficulty = match first.as_deref() {
+            None => Difficulty::Intermediate,
+            Some(arg) if arg.starts_with("--") => Difficulty::Intermediate,
+            Some(arg) => match Difficulty::from_name(arg) {
+                Some(difficulty) => {
+                    args.next();
+                    difficulty
+                }
+                None => {
+                    let mut next = |error| args.next().ok_or(MineSweeperError::InvalidConfig(error));
+                    let cols = next("missing cols")?.parse().map_err(|_| MineSweeperError::InvalidConfig("failed to convert cols"))?;
+                    let rows = next("missing rows")?.parse().map_err(|_| MineSweeperError::InvalidConfig("failed to convert rows"))?;
+                    let density = next("missing density")?;
+                    let density = match (density.parse(), density.parse()) {
+                        (Ok(mine_count), _) => Density::Count(mine_count),
+                        (_, Ok(concentration)) => Density::Concentration(concentration),
+                        _ => return Err(MineSweeperError::InvalidConfig("failed to convert density")),
+                    };
+                    Difficulty::Custom { cols, rows, density }
+                }
+            },
+        };
+
+        let mut config = GameConfig::new(difficulty)?;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let seed = args.next().ok_or(MineSweeperError::InvalidConfig("missing seed"))?;
+                    config.seed = Some(seed.parse().map_err(|_| MineSweeperError::InvalidConfig("failed to convert seed"))?);
+                }
+                "--first-click" => {
+                    let first_click = args.next().ok_or(MineSweeperError::InvalidConfig("missing first click"))?;
+                    config.first_click = parse_first_click(0, &first_click).map_err(|_| MineSweeperError::InvalidConfig("unknown first click"))?;
+                }
+                "--win-on-all-flagged" => config.win_on_all_flagged = true,
+                "--question-marks" => config.question_marks = true,
+                // read by `get_load_path` and `solver::from_args`
+                "--load" | "--solver" => {
+                    args.next();
+                }
+                _ => return Err(MineSweeperError::InvalidConfig("unknown argument")),
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn cols(&self) -> usize {
+        self.difficulty.size().0
+    }
+
+    pub fn rows(&self) -> usize {
+        self.difficulty.size().1
+    }
+
+    pub fn density(&self) -> Density {
+        self.difficulty.size().2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<GameConfig, MineSweeperError> {
+        GameConfig::parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parse_presets_and_custom_sizes() {
+        assert_eq!(parse("").unwrap(), GameConfig::default());
+        assert_eq!(parse("expert").unwrap().cols(), 30);
+        let config = parse("60 30 0.15 --seed 7 --first-click no_guess --question-marks").unwrap();
+        assert_eq!(config.difficulty(), Difficulty::Custom { cols: 60, rows: 30, density: Density::Concentration(0.15) });
+        assert_eq!((config.seed, config.first_click, config.question_marks), (Some(7), FirstClick::NoGuess, true));
+        assert_eq!(parse("9 9 10 --load game.save --solver alpha_ai").unwrap().density(), Density::Count(10));
+        assert_eq!(parse("3 3 10"), Err(MineSweeperError::InvalidConfig("more mines than cells")));
+        assert_eq!(parse("9 9"), Err(MineSweeperError::InvalidConfig("missing density")));
+        assert_eq!(parse("beginner --fast"), Err(MineSweeperError::InvalidConfig("unknown argument")));
+    }
+}
@@ -0,0 +1,111 @@
+//! Constraints from revealed numbers, "these unknown cells hold this many mines", and the rules that prove cells
+//! safe or mines with them. Shared by the deduction that checks generated boards and the constraint solvers.
+
+use std::collections::{HashMap, HashSet};
+
+/// How many times derived constraints are combined again before giving up
+const MAX_ROUNDS: usize = 4;
+
+/// The unknown cells around a number, sorted, and how many of them are mines
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Constraint {
+    pub cells: Vec<[usize;2]>,
+    pub mines: usize,
+}
+
+impl Constraint {
+    /// `None` without cells, as nothing can be learned then, or with more mines than cells, which means a flag is wrong
+    pub fn new(mut cells: Vec<[usize;2]>, mines: usize) -> Option<Self> {
+        if cells.is_empty() || mines > cells.len() {
+            return None;
+        }
+        cells.sort_unstable();
+        Some(Constraint { cells, mines })
+    }
+}
+
+/// The cells that are proven safe and the cells that are proven mines, using single constraints, subsets
+/// (a 1 inside a 2 leaves one mine for the rest of the 2) and differences (a 2 next to a 1 with only one cell
+/// of its own puts a mine there, as in 1-2-1). Both lists are sorted
+pub(crate) fn deduce(mut constraints: Vec<Constraint>) -> (Vec<[usize;2]>, Vec<[usize;2]>) {
+    let mut known: HashSet<Constraint> = constraints.iter().cloned().collect();
+    for _ in 0..MAX_ROUNDS {
+        let mut safes: HashSet<[usize;2]> = HashSet::new();
+        let mut mines: HashSet<[usize;2]> = HashSet::new();
+        for constraint in constraints.iter() {
+            if constraint.mines == 0 {
+                safes.extend(constraint.cells.iter());
+            } else if constraint.mines == constraint.cells.len() {
+                mines.extend(constraint.cells.iter());
+            }
+        }
+
+        let mut by_cell: HashMap<[usize;2], Vec<usize>> = HashMap::new();
+        for (i, constraint) in constraints.iter().enumerate() {
+            for cell in constraint.cells.iter() {
+                by_cell.entry(*cell).or_default().push(i);
+            }
+        }
+        let mut derived: Vec<Constraint> = Vec::new();
+        for (i, a) in constraints.iter().enumerate() {
+            let mut overlapping: Vec<usize> = a.cells.iter().flat_map(|cell| by_cell[cell].iter().copied()).filter(|j| *j != i).collect();
+            overlapping.sort_unstable();
+            overlapping.dedup();
+            for j in overlapping {
+                let b = &constraints[j];
+                let only_a = difference(&a.cells, &b.cells);
+                let only_b = difference(&b.cells, &a.cells);
+                if only_b.is_empty() {
+                    // a holds all of b, the rest of a has the mines b does not
+                    match (only_a.is_empty(), a.mines.checked_sub(b.mines)) {
+                        (false, Some(0)) => safes.extend(only_a),
+                        (false, Some(rest)) if rest == only_a.len() => mines.extend(only_a),
+                        (false, Some(rest)) => {
+                            let constraint = Constraint { cells: only_a, mines: rest };
+                            if known.insert(constraint.clone()) {
+                                derived.push(constraint);
+                            }
+                        }
+                        _ => {},
+                    }
+                } else if a.mines >= b.mines && a.mines - b.mines == only_a.len() {
+                    // the shared cells hold at most b.mines, so a only reaches its count if all of its own cells are mines
+                    mines.extend(only_a);
+                    safes.extend(only_b);
+                }
+            }
+        }
+
+        if !safes.is_empty() || !mines.is_empty() {
+            let mut safes: Vec<[usize;2]> = safes.into_iter().collect();
+            let mut mines: Vec<[usize;2]> = mines.into_iter().collect();
+            safes.sort_unstable();
+            mines.sort_unstable();
+            return (safes, mines);
+        }
+        if derived.is_empty() {
+            break;
+        }
+        constraints.extend(derived);
+    }
+    (Vec::new(), Vec::new())
+}
+
+/// The mine count on its own: with no mines left every unknown cell is safe, with as many as there are cells every one is a mine.
+/// It overlaps every other constraint, so it is only checked once they prove nothing
+pub(crate) fn deduce_from_count(unknown: Vec<[usize;2]>, mines_left: usize) -> (Vec<[usize;2]>, Vec<[usize;2]>) {
+    if unknown.is_empty() {
+        (Vec::new(), Vec::new())
+    } else if mines_left == 0 {
+        (unknown, Vec::new())
+    } else if mines_left == unknown.len() {
+        (Vec::new(), unknown)
+    } else {
+        (Vec::new(), Vec::new())
+    }
+}
+
+/// The cells of `a` that are not in `b`, both sorted
+fn difference(a: &[[usize;2]], b: &[[usize;2]]) -> Vec<[usize;2]> {
+    a.iter().copied().filter(|cell| b.binary_search(cell).is_err()).collect()
+}
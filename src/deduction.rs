@@ -0,0 +1,191 @@
+//! A deterministic solver that plays a board using only what a player could deduce,
+//! used to check that a generated board never needs a guess.
+
+use crate::board::neighbours;
+use crate::constraint::{deduce, deduce_from_count, Constraint};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Knowledge {
+    Unknown,
+    Revealed,
+    Mine,
+}
+
+pub(crate) struct Deduction<'a> {
+    mine_field: &'a [Vec<Option<u8>>],
+    knowledge: Vec<Vec<Knowledge>>,
+    safes_left: usize,
+    mines_left: usize,
+}
+
+impl<'a> Deduction<'a> {
+    /// Reveals `start` and keeps deducing until the board is solved or no more progress can be made
+    pub fn run(mine_field: &'a [Vec<Option<u8>>], start: [usize;2]) -> Self {
+        let mines = mine_field.iter().flatten().filter(|cell| cell.is_none()).count();
+        let mut deduction = Deduction {
+            mine_field,
+            knowledge: vec![vec![Knowledge::Unknown; mine_field[0].len()]; mine_field.len()],
+            safes_left: mine_field.len() * mine_field[0].len() - mines,
+            mines_left: mines,
+        };
+        if deduction.reveal(start) {
+            while deduction.safes_left > 0 && deduction.step() {}
+        }
+        deduction
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.safes_left == 0
+    }
+
+    /// Unknown cells next to a revealed number, i.e. where the solver got stuck
+    pub fn frontier(&self) -> Vec<[usize;2]> {
+        let mut frontier = Vec::new();
+        for y in 0..self.rows() {
+            for x in 0..self.cols() {
+                if self.knowledge[y][x] == Knowledge::Unknown && self.neighbours([x, y]).any(|n| self.get(n) == Knowledge::Revealed) {
+                    frontier.push([x, y]);
+                }
+            }
+        }
+        frontier
+    }
+
+    /// Unknown cells that no revealed number touches
+    pub fn interior(&self) -> Vec<[usize;2]> {
+        let mut interior = Vec::new();
+        for y in 0..self.rows() {
+            for x in 0..self.cols() {
+                if self.knowledge[y][x] == Knowledge::Unknown && !self.neighbours([x, y]).any(|n| self.get(n) == Knowledge::Revealed) {
+                    interior.push([x, y]);
+                }
+            }
+        }
+        interior
+    }
+
+    fn cols(&self) -> usize {
+        self.knowledge[0].len()
+    }
+
+    fn rows(&self) -> usize {
+        self.knowledge.len()
+    }
+
+    fn get(&self, position: [usize;2]) -> Knowledge {
+        self.knowledge[position[1]][position[0]]
+    }
+
+    fn neighbours(&self, position: [usize;2]) -> impl Iterator<Item = [usize;2]> {
+        neighbours(position, self.cols(), self.rows())
+    }
+
+    /// Reveals a cell and the area around it if it is a zero, returns false if it was a mine
+    fn reveal(&mut self, position: [usize;2]) -> bool {
+        if self.mine_field[position[1]][position[0]].is_none() {
+            return false;
+        }
+        let mut queue = vec![position];
+        while let Some(cell) = queue.pop() {
+            if self.get(cell) != Knowledge::Unknown {
+                continue;
+            }
+            self.knowledge[cell[1]][cell[0]] = Knowledge::Revealed;
+            self.safes_left -= 1;
+            if self.mine_field[cell[1]][cell[0]] == Some(0) {
+                queue.extend(self.neighbours(cell));
+            }
+        }
+        true
+    }
+
+    fn mark_mine(&mut self, position: [usize;2]) {
+        if self.get(position) == Knowledge::Unknown {
+            self.knowledge[position[1]][position[0]] = Knowledge::Mine;
+            self.mines_left -= 1;
+        }
+    }
+
+    fn constraints(&self) -> Vec<Constraint> {
+        let mut constraints = Vec::new();
+        for y in 0..self.rows() {
+            for x in 0..self.cols() {
+                if self.knowledge[y][x] != Knowledge::Revealed {
+                    continue;
+                }
+                let value = self.mine_field[y][x].unwrap() as usize;
+                let mut cells = Vec::new();
+                let mut known_mines = 0;
+                for n in self.neighbours([x, y]) {
+                    match self.get(n) {
+                        Knowledge::Unknown => cells.push(n),
+                        Knowledge::Mine => known_mines += 1,
+                        Knowledge::Revealed => {},
+                    }
+                }
+                if let Some(constraint) = Constraint::new(cells, value - known_mines) {
+                    constraints.push(constraint);
+                }
+            }
+        }
+        constraints
+    }
+
+    /// Applies every deduction it can find once, returns false if it found nothing
+    fn step(&mut self) -> bool {
+        let (safes, mines) = self.deduce();
+        if safes.is_empty() && mines.is_empty() {
+            return false;
+        }
+        for mine in mines {
+            self.mark_mine(mine);
+        }
+        for safe in safes {
+            // a deduced safe cell is never a mine, so this cannot fail
+            self.reveal(safe);
+        }
+        true
+    }
+
+    /// What the numbers prove, or failing that what the total mine count proves
+    fn deduce(&self) -> (Vec<[usize;2]>, Vec<[usize;2]>) {
+        match deduce(self.constraints()) {
+            (safes, mines) if safes.is_empty() && mines.is_empty() => {
+                let unknown: Vec<[usize;2]> = (0..self.rows())
+                    .flat_map(|y| (0..self.cols()).map(move |x| [x, y]))
+                    .filter(|cell| self.get(*cell) == Knowledge::Unknown)
+                    .collect();
+                deduce_from_count(unknown, self.mines_left)
+            }
+            proven => proven,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn solve_only_what_can_be_deduced() {
+        let solvable = Board::parse("
+            0 1 X
+            0 1 1
+            0 0 0
+        ").unwrap();
+        assert!(Deduction::run(&solvable.mine_field, [0, 0]).is_solved());
+
+        // the mine is in one of the two cells next to the ones, there is no telling which
+        let guess = Board::parse("
+            0 1 X 1
+            0 1 1 1
+        ").unwrap();
+        let deduction = Deduction::run(&guess.mine_field, [0, 0]);
+        assert!(!deduction.is_solved());
+        assert_eq!(deduction.frontier(), vec![[2, 0], [2, 1]]);
+        assert_eq!(deduction.interior(), vec![[3, 0], [3, 1]]);
+        // revealing a mine first solves nothing
+        assert!(!Deduction::run(&guess.mine_field, [2, 0]).is_solved());
+    }
+}
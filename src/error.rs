@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// Why an operation on a board or game could not be carried out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MineSweeperError {
+    /// The position is outside of the board
+    OutOfBounds([usize;2]),
+    /// The cursor is above or left of the board, so it is on no cell at all
+    CursorOffBoard,
+    /// The cell has already been revealed
+    AlreadyRevealed([usize;2]),
+    /// The cell has to be unflagged first
+    Flagged([usize;2]),
+    /// The cell has to be revealed first
+    NotRevealed([usize;2]),
+    /// Chording needs exactly as many flags around a number as the number itself
+    FlagCountMismatch { position: [usize;2], value: u8, flags: usize },
+    /// The game has already been won or lost
+    GameOver,
+    /// There are no moves to undo
+    NothingToUndo,
+    /// There are no undone moves to redo
+    NothingToRedo,
+    /// The settings or arguments describe a game that cannot be created
+    InvalidConfig(&'static str),
+    /// A file could not be understood, `line` starts at 1
+    Parse { line: usize, reason: String },
+    /// A file could not be read or written
+    Io(String),
+}
+
+impl From<std::io::Error> for MineSweeperError {
+    fn from(error: std::io::Error) -> Self {
+        MineSweeperError::Io(error.to_string())
+    }
+}
+
+impl fmt::Display for MineSweeperError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MineSweeperError::OutOfBounds(position) => write!(f, "{:?} is outside of the board", position),
+            MineSweeperError::CursorOffBoard => write!(f, "the cursor is not on the board"),
+            MineSweeperError::AlreadyRevealed(position) => write!(f, "{:?} is already revealed", position),
+            MineSweeperError::Flagged(position) => write!(f, "{:?} is flagged", position),
+            MineSweeperError::NotRevealed(position) => write!(f, "{:?} is not revealed", position),
+            MineSweeperError::FlagCountMismatch { position, value, flags } => write!(f, "{:?} is a {} with {} flags around it", position, value, flags),
+            MineSweeperError::GameOver => write!(f, "the game is over"),
+            MineSweeperError::NothingToUndo => write!(f, "there is nothing to undo"),
+            MineSweeperError::NothingToRedo => write!(f, "there is nothing to redo"),
+            MineSweeperError::InvalidConfig(reason) => write!(f, "invalid configuration: {}", reason),
+            MineSweeperError::Parse { line, reason } => write!(f, "line {}: {}", line, reason),
+            MineSweeperError::Io(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for MineSweeperError {}
@@ -0,0 +1,164 @@
+use graphics::{color::BLACK, color::WHITE};
+use opengl_graphics::GlGraphics;
+use piston::RenderArgs;
+use std::time::Instant;
+use crate::error::MineSweeperError;
+use crate::mine_sweeper::{GameState, MineSweeper, MoveOutcome, ShownState};
+
+pub const COLORS: [[f32; 4]; 8] = [
+    [0.0,0.0,1.0,1.0], // Blue
+    [0.0,1.0,0.0,1.0], // Green
+    [1.0,0.0,0.0,1.0], // Red
+    [1.0,0.4,0.9,1.0], // Pink
+    [0.0,0.0,0.6,1.0], // Dark Blue
+    [0.0,0.6,0.0,1.0], // Dark green
+    [0.6,0.0,0.0,1.0], // Dark Red
+    [0.65,0.0,0.55,1.0], // purple
+    ];
+
+pub struct ApperanceSettings {
+    pub square_size: f64,
+    pub square_color: [f32;4],
+    pub line_radius: f64, // TODO: rename to gap_radius?
+    pub background_color: [f32;4],
+    /// Color of the dot on a question mark
+    pub marked_color: [f32;4],
+    /// Color of revealed mines, only seen when the board is shown after a loss
+    pub mine_color: [f32;4],
+    /// Fill the window green or red once the game is won or lost instead of showing the board
+    pub cover_finished_games: bool,
+}
+impl Default for ApperanceSettings{
+    fn default() -> Self {
+        Self {
+            square_size: 20.0,
+            square_color: WHITE,
+            line_radius: 1.0,
+            background_color: BLACK,
+            marked_color: [1.0, 0.8, 0.0, 1.0],
+            mine_color: [0.4, 0.4, 0.4, 1.0],
+            cover_finished_games: true,
+        }
+    }
+}
+
+/// Draws a `MineSweeper` with piston and turns window coordinates into cell positions
+pub struct Frontend {
+    pub apperance: ApperanceSettings,
+    pub scale: [f64;2],
+    start_time: Instant,
+}
+
+impl Default for Frontend {
+    fn default() -> Self {
+        Frontend::new(ApperanceSettings::default())
+    }
+}
+
+impl Frontend {
+    pub fn new(apperance: ApperanceSettings) -> Self {
+        Frontend {
+            apperance,
+            scale: [1f64;2],
+            start_time: Instant::now(),
+        }
+    }
+
+    /// The unscaled size of the window needed to fit every cell of `mine_sweeper`
+    pub fn window_size(&self, mine_sweeper: &MineSweeper) -> [f64;2] {
+        [self.apperance.square_size * mine_sweeper.cols() as f64, self.apperance.square_size * mine_sweeper.rows() as f64]
+    }
+
+    pub fn render(&self, mine_sweeper: &MineSweeper, args: &RenderArgs, gl: &mut GlGraphics) {
+        use graphics::*;
+
+        let c = gl.draw_begin(args.viewport());
+        match mine_sweeper.game_state {
+            GameState::Won if self.apperance.cover_finished_games => {
+                clear([0.0, 1.0, 0.0, 1.0], gl);
+            }
+            GameState::Lost if self.apperance.cover_finished_games => {
+                clear([1.0, 0.0, 0.0, 1.0], gl);
+            }
+            _ => {
+                clear(self.apperance.background_color, gl);
+
+                let windows_scaling: [f64;2] = [c.get_view_size()[0] / c.viewport.unwrap().draw_size[0] as f64, c.get_view_size()[1] / c.viewport.unwrap().draw_size[1] as f64];
+                let transform = c.transform.scale(windows_scaling[0], windows_scaling[1]).scale(self.scale[0], self.scale[1]);
+
+                // Draw the cells
+                for (y, row) in mine_sweeper.states.iter().enumerate() {
+                    for (x, col) in row.iter().enumerate() {
+                        let rect: [f64;4] = [
+                            (self.apperance.square_size * x as f64) + self.apperance.line_radius,
+                            (self.apperance.square_size * y as f64) + self.apperance.line_radius,
+                            self.apperance.square_size - self.apperance.line_radius * 2.0,
+                            self.apperance.square_size - self.apperance.line_radius * 2.0];
+                        match col {
+                            // draw White square
+                            ShownState::Hidden => {
+                                rectangle(self.apperance.square_color, rect, transform,gl);
+                            },
+                            ShownState::Revealed => {
+                                // unless the cell is 0, draw the character
+                                match mine_sweeper.board().get([x, y]) {
+                                    Some(0) => {},
+                                    Some(sq) => rectangle(COLORS[(sq -1) as usize], rect, transform, gl),
+                                    None => rectangle(self.apperance.mine_color, rect, transform, gl),
+                                }
+                            },
+                            // a hidden square with a dot in the middle
+                            ShownState::Marked => {
+                                rectangle(self.apperance.square_color, rect, transform, gl);
+                                let dot = self.apperance.square_size / 4.0;
+                                rectangle(self.apperance.marked_color, [rect[0] + dot, rect[1] + dot, rect[2] - dot * 2.0, rect[3] - dot * 2.0], transform, gl);
+                            },
+                            // draw a flag here
+                            ShownState::Flagged => {
+                                let time: f32 = self.start_time.elapsed().as_secs_f32();
+                                let color: [f32;4] = [(time.sin()+1.0)/2.0, ((time+1.57).sin()+1.0)/2.0, ((time+std::f32::consts::PI).sin()+1.0)/2.0, 1.0];
+                                rectangle(color, rect, transform, gl);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        gl.draw_end();
+    }
+
+    /// `cursor` is in window coordinates, i.e. before undoing `scale`
+    pub fn left_click(&self, mine_sweeper: &mut MineSweeper, cursor: [f64;2]) -> Result<MoveOutcome, MineSweeperError> {
+        let cell_pos = self.get_cell_from_position(mine_sweeper, cursor)?;
+        println!("L click at: {:?}={:?}, is {:?}", cursor, cell_pos, mine_sweeper.board().get(cell_pos));
+        mine_sweeper.left_click_cell(cell_pos)
+    }
+
+    /// `cursor` is in window coordinates, i.e. before undoing `scale`
+    pub fn right_click(&self, mine_sweeper: &mut MineSweeper, cursor: [f64;2]) -> Result<MoveOutcome, MineSweeperError> {
+        let cell_pos = self.get_cell_from_position(mine_sweeper, cursor)?;
+        println!("R click at: {:?}={:?}, is {:?}", cursor, cell_pos, mine_sweeper.board().get(cell_pos));
+        mine_sweeper.right_click_cell(cell_pos)
+    }
+
+    /// `cursor` is in window coordinates, i.e. before undoing `scale`
+    pub fn chord(&self, mine_sweeper: &mut MineSweeper, cursor: [f64;2]) -> Result<MoveOutcome, MineSweeperError> {
+        let cell_pos = self.get_cell_from_position(mine_sweeper, cursor)?;
+        println!("Chord at: {:?}={:?}, is {:?}", cursor, cell_pos, mine_sweeper.board().get(cell_pos));
+        mine_sweeper.chord_cell(cell_pos)
+    }
+
+    fn get_cell_from_position(&self, mine_sweeper: &MineSweeper, position: [f64;2]) -> Result<[usize;2], MineSweeperError> {
+        let position = [position[0] / self.scale[0] / self.apperance.square_size, position[1] / self.scale[1] / self.apperance.square_size];
+        // negative positions saturate to 0 when cast, so they have to be caught before
+        if position[0] < 0.0 || position[1] < 0.0 {
+            return Err(MineSweeperError::CursorOffBoard);
+        }
+        let cell_pos = [position[0].trunc() as usize, position[1].trunc() as usize];
+        if cell_pos[0] >= mine_sweeper.cols() || cell_pos[1] >= mine_sweeper.rows() {
+            return Err(MineSweeperError::OutOfBounds(cell_pos));
+        }
+        Ok(cell_pos)
+    }
+}
@@ -1,344 +1,1002 @@
-use graphics::{color::BLACK, color::WHITE, ellipse, rectangle};
-use opengl_graphics::{GlGraphics, OpenGL};
-use piston::RenderArgs;
-use std::time::SystemTime;
-use std::char::from_digit;
-use rand::prelude::*;
-
-pub const COLORS: [[f32; 4]; 8] = [
-    [0.0,0.0,1.0,1.0], // Blue
-    [0.0,1.0,0.0,1.0], // Green
-    [1.0,0.0,0.0,1.0], // Red
-    [1.0,0.4,0.9,1.0], // Pink
-    [0.0,0.0,0.6,1.0], // Dark Blue
-    [0.0,0.6,0.0,1.0], // Dark green
-    [0.6,0.0,0.0,1.0], // Dark Red
-    [0.65,0.0,0.55,1.0], // purple
-    ];
-
-pub const NEIGHBOURS: [[i8;2];8] = [[-1, -1], [-1, 0], [-1, 1], [0, -1], [0, 1], [1, -1], [1, 0], [1, 1] ];
+use std::path::Path;
+use std::time::{Duration, Instant};
+use rand::RngCore;
+use crate::board::{neighbours, parse_cells, Board, Density, FirstClick};
+use crate::config::GameConfig;
+use crate::error::MineSweeperError;
+use crate::replay::{Recorder, Replay, ReplayAction, ReplaySource};
+use crate::save::SavedGame;
+use crate::view::PlayerView;
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum ShownState {
     Hidden,
     Revealed,
-    Flagged
+    Flagged,
+    /// A question mark, placed by flagging a flagged cell when `MineSweeper::question_marks` is on
+    Marked,
 }
+
+impl ShownState {
+    /// Hidden and marked cells can both be revealed
+    pub fn is_hidden(self) -> bool {
+        matches!(self, ShownState::Hidden | ShownState::Marked)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum GameState {
     Running,
     Won,
     Lost
 }
 
-pub struct ApperanceSettings {
-    pub square_size: f64,
-    pub square_color: [f32;4],
-    pub line_radius: f64, // TODO: rename to gap_radius?
-    pub background_color: [f32;4],
+/// A move a player or solver can make
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    Reveal([usize;2]),
+    /// Flags a hidden cell or unflags a flagged one
+    Flag([usize;2]),
+    Chord([usize;2]),
 }
-impl Default for ApperanceSettings{
-    fn default() -> Self {
-        Self {
-            square_size: 20.0,
-            square_color: WHITE,
-            line_radius: 1.0,
-            background_color: BLACK
+
+/// What changed on the board because of one action, so a solver does not have to rescan `states`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveOutcome {
+    /// Every newly revealed cell with its number, or `None` for a mine
+    pub revealed: Vec<([usize;2], Option<u8>)>,
+    pub flagged: Vec<[usize;2]>,
+    pub unflagged: Vec<[usize;2]>,
+    pub marked: Vec<[usize;2]>,
+    /// Question marks removed by cycling the flag or by revealing the cell
+    pub unmarked: Vec<[usize;2]>,
+    pub hit_mine: bool,
+    /// The state of the game after the action
+    pub game_state: GameState,
+}
+
+impl MoveOutcome {
+    /// Nothing on the board changed
+    pub fn is_empty(&self) -> bool {
+        self.revealed.is_empty() && self.flagged.is_empty() && self.unflagged.is_empty() && self.marked.is_empty() && self.unmarked.is_empty()
+    }
+}
+
+/// How long a game has been played, on a monotonic clock that only runs between the first reveal and the end of the game
+#[derive(Debug, Copy, Clone, Default)]
+pub struct GameClock {
+    /// Time played before `running_since`
+    earlier: Duration,
+    running_since: Option<Instant>,
+    started: bool,
+}
+
+impl GameClock {
+    /// A stopped clock that continues from `elapsed` once started, for resumed games
+    pub fn resumed(elapsed: Duration) -> Self {
+        GameClock { earlier: elapsed, running_since: None, started: elapsed > Duration::from_millis(0) }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.earlier + self.running_since.map(|since| since.elapsed()).unwrap_or_default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// Whether the clock has ever run, it stays true after the game is over
+    pub fn has_started(&self) -> bool {
+        self.started
+    }
+
+    fn start(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some(Instant::now());
+            self.started = true;
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.earlier += since.elapsed();
         }
     }
 }
 
+/// How many moves of each kind were made, for scoring and statistics
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MoveCounts {
+    /// Every move tried while the game was running, including the ones that failed or changed nothing
+    pub clicks: usize,
+    pub reveals: usize,
+    /// Flag toggles, also the ones that remove a flag or question mark
+    pub flags: usize,
+    pub chords: usize,
+}
+
+/// A move that can be undone
+struct HistoryEntry {
+    action: Action,
+    outcome: MoveOutcome,
+    previous_state: GameState,
+    /// The mine layout before the first reveal moved mines around
+    board_before: Option<Board>,
+}
+
+/// The rules of the game, without any rendering
 pub struct MineSweeper {
-    pub mine_field: Vec<Vec<Option<u8>>>,
-    mine_count: usize,
+    board: Board,
+    /// Flags that are on a mine, never shown to the player
     mines_flagged: usize,
+    flags_placed: usize,
+    safe_cells_remaining: usize,
     pub states: Vec<Vec<ShownState>>,
-    pub apperance: ApperanceSettings,
-    pub scale: [f64;2],
     pub game_state: GameState,
-    pub start_time: SystemTime,
+    clock: GameClock,
+    move_counts: MoveCounts,
+    /// Only has an effect before the first reveal
+    pub first_click: FirstClick,
+    /// Also win once every mine is flagged and no other cell is, instead of only when every safe cell is revealed
+    pub win_on_all_flagged: bool,
+    /// Flagging cycles hidden, flagged, question mark and back to hidden instead of skipping the question mark
+    pub question_marks: bool,
+    first_reveal_done: bool,
+    history: Vec<HistoryEntry>,
+    redo_stack: Vec<Action>,
+    undo_used: bool,
+    recorder: Option<Recorder>,
+    /// What was shown when a game was loaded or built from a position, `None` if it started with every cell hidden
+    start: Option<Vec<Vec<ShownState>>>,
 }
 
 impl Default for MineSweeper {
-    /// Returns 16*16 with 0.15 concentration and default apperance
+    /// Returns 16*16 with 0.15 concentration
     fn default() -> Self {
-        MineSweeper::new(16, 16, 0.15, ApperanceSettings::default())
+        MineSweeper::new(16, 16, 0.15)
     }
 }
 
 impl MineSweeper {
-    pub fn new(cols: usize, rows: usize, concentration: f64, appearance: ApperanceSettings) -> Self {
-        let mut mine_count: usize = 0;
-        // Create a new game and run it.
+    pub fn new(cols: usize, rows: usize, concentration: f64) -> Self {
+        MineSweeper::from_board(Board::new(cols, rows, concentration))
+    }
+
+    /// The same seed, dimensions and concentration always give the same mine layout
+    pub fn with_seed(cols: usize, rows: usize, concentration: f64, seed: u64) -> Self {
+        MineSweeper::from_board(Board::with_seed(cols, rows, concentration, seed))
+    }
+
+    /// Fails if `density` asks for more mines than there are cells
+    pub fn generate(cols: usize, rows: usize, density: Density, seed: u64) -> Result<Self, MineSweeperError> {
+        Ok(MineSweeper::from_board(Board::generate(cols, rows, density, seed)?))
+    }
+
+    /// Starts a game with the size and rules of `config`, drawing a seed if it has none
+    pub fn from_config(config: &GameConfig) -> Result<Self, MineSweeperError> {
+        let seed = config.seed.unwrap_or_else(|| rand::thread_rng().next_u64());
+        let mut mine_sweeper = MineSweeper::generate(config.cols(), config.rows(), config.density(), seed)?;
+        mine_sweeper.first_click = config.first_click;
+        mine_sweeper.win_on_all_flagged = config.win_on_all_flagged;
+        mine_sweeper.question_marks = config.question_marks;
+        Ok(mine_sweeper)
+    }
+
+    /// Seeds the board from `rng`, the seed is still recorded on the game
+    pub fn from_rng<R: RngCore + ?Sized>(cols: usize, rows: usize, concentration: f64, rng: &mut R) -> Self {
+        MineSweeper::from_board(Board::from_rng(cols, rows, concentration, rng))
+    }
+
+    pub fn from_board(board: Board) -> Self {
+        let (cols, rows) = (board.cols(), board.rows());
+        let safe_cells_remaining = cols * rows - board.mine_count();
         MineSweeper {
-            mine_field: generate_random_grid(cols, rows, concentration, &mut mine_count),
-            mine_count,
+            board,
             mines_flagged: 0,
+            flags_placed: 0,
+            safe_cells_remaining,
             states: vec![vec![ShownState::Hidden; cols]; rows],
-            apperance: appearance,
-            scale: [1f64;2], // No real need to have these in the MineSweeper struct, could just compute it every run
             game_state: GameState::Running,
-            start_time: SystemTime::now(),
+            clock: GameClock::default(),
+            move_counts: MoveCounts::default(),
+            first_click: FirstClick::default(),
+            win_on_all_flagged: false,
+            question_marks: false,
+            first_reveal_done: false,
+            history: Vec::new(),
+            redo_stack: Vec::new(),
+            undo_used: false,
+            recorder: None,
+            start: None,
         }
     }
 
+    /// Where the mines are, solvers should only look at `view`
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// What a player can see of the game
+    pub fn view(&self) -> PlayerView<'_> {
+        PlayerView::new(self)
+    }
 
     pub fn cols(&self) -> usize {
-        return self.states[0].len()
+        self.states[0].len()
     }
 
     pub fn rows(&self) -> usize {
-        return self.states.len()
-    }
-
-    pub fn render(&mut self, args: &RenderArgs, gl: &mut GlGraphics) {
-        use graphics::*;
-
-        let c = gl.draw_begin(args.viewport());
-        match self.game_state {
-            GameState::Running => {
-                clear(self.apperance.background_color, gl);
-
-                let windows_scaling: [f64;2] = [c.get_view_size()[0] / c.viewport.unwrap().draw_size[0] as f64, c.get_view_size()[1] / c.viewport.unwrap().draw_size[1] as f64];
-                let transform = c.transform.scale(windows_scaling[0], windows_scaling[1]).scale(self.scale[0], self.scale[1]);
-
-                // Draw the cells
-                for (y, row) in self.states.iter().enumerate() {
-                    for (x, col) in row.iter().enumerate() {
-                        let rect: [f64;4] = [
-                            (self.apperance.square_size * x as f64) + self.apperance.line_radius, 
-                            (self.apperance.square_size * y as f64) + self.apperance.line_radius, 
-                            self.apperance.square_size - self.apperance.line_radius * 2.0, 
-                            self.apperance.square_size - self.apperance.line_radius * 2.0];
-                        match col {
-                            // draw White square
-                            ShownState::Hidden => { 
-                                rectangle(WHITE, rect, transform,gl);
-                            },
-                            ShownState::Revealed => {
-                                if let Some(sq) = &self.mine_field[y][x] { 
-                                    // unless the cell is 0, draw the character
-                                    if *sq != 0 {
-                                        //let color: [f32;4] = [(*sq%2).into(), f32::from(*sq%3)/2.0, f32::from(*sq%4)/3.0, 1.0];
-                                        rectangle(COLORS[(*sq -1) as usize], rect, transform, gl);
-                                    }
-                                }
-                                else {
-                                    // Draw bomb
-                                    // ellipse(WHITE, rect, transform, gl);
-                                }
-                            },
-                            // draw a flag here
-                            ShownState::Flagged => {
-                                let time: f32 = SystemTime::now().duration_since(self.start_time).unwrap().as_secs_f32();
-                                let color: [f32;4] = [(time.sin()+1.0)/2.0, ((time+1.57).sin()+1.0)/2.0, ((time+3.14).sin()+1.0)/2.0, 1.0];
-                                rectangle(color, rect, transform, gl);
-                            }
-                        }
-                    }
-                }
-            }
-            GameState::Won => {
-                clear([0.0, 1.0, 0.0, 1.0], gl);
-            }
-            GameState::Lost => {
-                clear([1.0, 0.0, 0.0, 1.0], gl);
-            }
+        self.states.len()
+    }
+
+    pub fn mine_count(&self) -> usize {
+        self.board.mine_count()
+    }
+
+    /// Flags on the board, whether they are correct or not
+    pub fn flags_placed(&self) -> usize {
+        self.flags_placed
+    }
+
+    /// The mine counter a player sees, can be negative when there are more flags than mines
+    pub fn mines_left(&self) -> isize {
+        self.mine_count() as isize - self.flags_placed as isize
+    }
+
+    /// Safe cells that are yet to be revealed, the game is won when this reaches zero
+    pub fn safe_cells_remaining(&self) -> usize {
+        self.safe_cells_remaining
+    }
+
+    /// Runs from the first reveal until the game is won or lost
+    pub fn clock(&self) -> &GameClock {
+        &self.clock
+    }
+
+    /// Time played so far, see `clock`
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    /// Moves made so far, undone moves are still counted
+    pub fn move_counts(&self) -> MoveCounts {
+        self.move_counts
+    }
+
+    /// 3BV divided by the clicks it took, 1.0 means no click was wasted. `None` until the game is won
+    pub fn efficiency(&self) -> Option<f64> {
+        if self.game_state != GameState::Won || self.move_counts.clicks == 0 {
+            return None;
         }
+        Some(self.board.metrics().three_bv as f64 / self.move_counts.clicks as f64)
+    }
 
-        gl.draw_end();
+    /// 3BV solved per second of the game clock. `None` until the game is won
+    pub fn three_bv_per_second(&self) -> Option<f64> {
+        let seconds = self.elapsed().as_secs_f64();
+        if self.game_state != GameState::Won || seconds == 0.0 {
+            return None;
+        }
+        Some(self.board.metrics().three_bv as f64 / seconds)
     }
 
-    pub fn left_click(&mut self, mouse_pos: [f64;2]) {
-        let cell_pos = self.get_cell_from_position(mouse_pos);
-        println!("L click at: {:?}={:?}, is {:?}", mouse_pos, cell_pos, self.mine_field[cell_pos[1]][cell_pos[0]]);
-        if let Ok(hit_mine) = self.reveal_cell(cell_pos) {
-            if hit_mine {
-                self.game_state = GameState::Lost;
-            }
+    /// The seed of the mine layout, `Board::generate` with it and `board().density()` gives the board as it was
+    /// before the first reveal moved any mines. Use `replay` to play the game itself back
+    pub fn seed(&self) -> Option<u64> {
+        self.board.seed()
+    }
+
+    /// Reveals a cell, and the area around it if it is a zero
+    pub fn left_click_cell(&mut self, cell_pos: [usize;2]) -> Result<MoveOutcome, MineSweeperError> {
+        self.act(Action::Reveal(cell_pos))
+    }
+
+    /// Flags a hidden cell or unflags a flagged one, see `question_marks`
+    pub fn right_click_cell(&mut self, cell_pos: [usize;2]) -> Result<MoveOutcome, MineSweeperError> {
+        self.act(Action::Flag(cell_pos))
+    }
+
+    /// Reveals every hidden neighbour of a revealed number once as many of its neighbours are flagged,
+    /// a wrongly placed flag loses the game
+    pub fn chord_cell(&mut self, cell_pos: [usize;2]) -> Result<MoveOutcome, MineSweeperError> {
+        self.act(Action::Chord(cell_pos))
+    }
+
+    /// Makes a move and records it so it can be undone, this clears the moves that could be redone
+    pub fn act(&mut self, action: Action) -> Result<MoveOutcome, MineSweeperError> {
+        let outcome = self.record(action)?;
+        self.redo_stack.clear();
+        if let Some(recorder) = &mut self.recorder {
+            recorder.push(ReplayAction::Act(action));
         }
+        Ok(outcome)
     }
 
-    pub fn left_click_cell(&mut self, cell_pos: [usize;2]) {
-        //println!("L click at: {:?}, is {:?}", cell_pos, self.mine_field[cell_pos[1]][cell_pos[0]]);
-        if let Ok(hit_mine) = self.reveal_cell(cell_pos) {
-            if hit_mine {
-                self.game_state = GameState::Lost;
+    /// Takes back the last move, even one that lost or won the game, and returns it
+    pub fn undo(&mut self) -> Result<Action, MineSweeperError> {
+        let entry = self.history.pop().ok_or(MineSweeperError::NothingToUndo)?;
+        for (cell, value) in entry.outcome.revealed.iter() {
+            self.states[cell[1]][cell[0]] = ShownState::Hidden;
+            if value.is_some() {
+                self.safe_cells_remaining += 1;
             }
         }
+        for cell in entry.outcome.marked.iter() {
+            self.states[cell[1]][cell[0]] = ShownState::Hidden;
+        }
+        for cell in entry.outcome.flagged.iter() {
+            self.states[cell[1]][cell[0]] = ShownState::Hidden;
+            self.flags_placed -= 1;
+        }
+        for cell in entry.outcome.unmarked.iter() {
+            self.states[cell[1]][cell[0]] = ShownState::Marked;
+        }
+        for cell in entry.outcome.unflagged.iter() {
+            self.states[cell[1]][cell[0]] = ShownState::Flagged;
+            self.flags_placed += 1;
+        }
+        if let Some(board) = entry.board_before {
+            self.board = board;
+            self.first_reveal_done = false;
+        }
+        self.count_correct_flags();
+        self.game_state = entry.previous_state;
+        // taking back the move that ended the game lets the clock run again
+        if self.game_state == GameState::Running && self.clock.has_started() {
+            self.clock.start();
+        }
+        self.redo_stack.push(entry.action);
+        self.undo_used = true;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.push(ReplayAction::Undo);
+        }
+        Ok(entry.action)
     }
 
+    /// Makes the last undone move again
+    pub fn redo(&mut self) -> Result<MoveOutcome, MineSweeperError> {
+        let action = *self.redo_stack.last().ok_or(MineSweeperError::NothingToRedo)?;
+        let outcome = self.record(action)?;
+        self.redo_stack.pop();
+        if let Some(recorder) = &mut self.recorder {
+            recorder.push(ReplayAction::Redo);
+        }
+        Ok(outcome)
+    }
 
-    pub fn right_click(&mut self, mouse_pos: [f64;2]) {
-        let cell_pos = self.get_cell_from_position(mouse_pos);
-        println!("R click at: {:?}={:?}, is {:?}", mouse_pos, cell_pos, self.mine_field[cell_pos[1]][cell_pos[0]]);
-        if let Ok(res) = self.toggle_flag_cell(cell_pos) {
-            if res.1 {
-                self.mines_flagged = if res.0 == ShownState::Flagged {self.mines_flagged+1} else {self.mines_flagged-1};
-                if self.mines_flagged >= self.mine_count {
-                    self.game_state = GameState::Won;
-                }
-            }
+    /// Whether any move has been undone in this game, so its score can be kept apart
+    pub fn undo_used(&self) -> bool {
+        self.undo_used
+    }
+
+    /// Records every following move, undo and redo. The moves made so far are included without their timing,
+    /// moves that can still be redone as well, each followed by the undo that took it back
+    pub fn start_recording(&mut self) {
+        let made = self.history.iter().map(|entry| ReplayAction::Act(entry.action));
+        // the last move on the redo stack was undone last, so it was made first
+        let undone = self.redo_stack.iter().rev().map(|action| ReplayAction::Act(*action));
+        let undos = self.redo_stack.iter().map(|_| ReplayAction::Undo);
+        self.recorder = Some(Recorder::new(made.chain(undone).chain(undos).collect()));
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// The recording so far, `None` if `start_recording` was never called
+    pub fn replay(&self) -> Option<Replay> {
+        let recorder = self.recorder.as_ref()?;
+        let source = match (&self.start, self.board.seed(), self.board.density()) {
+            // the seed would give an untouched board, the moves that led to the start are not known
+            (Some(states), _, _) => ReplaySource::Position { mine_field: self.board.mine_field.clone(), states: states.clone() },
+            (None, Some(seed), Some(density)) => ReplaySource::Seed { seed, density, first_click: self.first_click },
+            (None, _, _) => ReplaySource::Layout(self.board.mine_field.clone()),
+        };
+        Some(Replay {
+            cols: self.cols(),
+            rows: self.rows(),
+            source,
+            win_on_all_flagged: self.win_on_all_flagged,
+            question_marks: self.question_marks,
+            actions: recorder.actions().to_vec(),
+        })
+    }
+
+    /// Everything needed to resume this game later, without the undo history
+    pub fn saved_game(&self) -> SavedGame {
+        SavedGame {
+            cols: self.cols(),
+            rows: self.rows(),
+            seed: self.board.seed(),
+            density: self.board.density(),
+            first_click: self.first_click,
+            win_on_all_flagged: self.win_on_all_flagged,
+            question_marks: self.question_marks,
+            first_reveal_done: self.first_reveal_done,
+            game_state: self.game_state,
+            elapsed: self.clock.elapsed(),
+            move_counts: self.move_counts,
+            flags_placed: self.flags_placed,
+            mines_flagged: self.mines_flagged,
+            safe_cells_remaining: self.safe_cells_remaining,
+            mine_field: self.board.mine_field.clone(),
+            states: self.states.clone(),
         }
     }
 
-    pub fn right_click_cell(&mut self, cell_pos: [usize;2]) {
-        //println!("R click at: {:?}, is {:?}", cell_pos, self.mine_field[cell_pos[1]][cell_pos[0]]);
-        if let Ok(res) = self.toggle_flag_cell(cell_pos) {
-            if res.1 {
-                self.mines_flagged = if res.0 == ShownState::Flagged {self.mines_flagged+1} else {self.mines_flagged-1};
-                if self.mines_flagged >= self.mine_count {
-                    self.game_state = GameState::Won;
-                }
-            }
+    /// Resumes a saved game, the clock continues from where it was saved.
+    /// Fails if the states or counters do not fit the layout
+    pub fn from_saved_game(saved: SavedGame) -> Result<Self, MineSweeperError> {
+        let (cols, rows) = (saved.cols, saved.rows);
+        let mut board = Board::from_mine_field(saved.mine_field)?;
+        board.set_origin(saved.seed, saved.density);
+        if board.cols() != cols || board.rows() != rows || saved.states.len() != rows || saved.states.iter().any(|row| row.len() != cols) {
+            return Err(MineSweeperError::InvalidConfig("the states do not match the size of the board"));
+        }
+        let mut mine_sweeper = MineSweeper::from_board(board);
+        mine_sweeper.states = saved.states;
+        mine_sweeper.first_click = saved.first_click;
+        mine_sweeper.win_on_all_flagged = saved.win_on_all_flagged;
+        mine_sweeper.question_marks = saved.question_marks;
+        mine_sweeper.first_reveal_done = saved.first_reveal_done;
+        mine_sweeper.game_state = saved.game_state;
+        mine_sweeper.move_counts = saved.move_counts;
+        mine_sweeper.clock = GameClock::resumed(saved.elapsed);
+        if mine_sweeper.game_state == GameState::Running && mine_sweeper.clock.has_started() {
+            mine_sweeper.clock.start();
+        }
+
+        mine_sweeper.count_shown_cells();
+        if (mine_sweeper.flags_placed, mine_sweeper.mines_flagged, mine_sweeper.safe_cells_remaining) != (saved.flags_placed, saved.mines_flagged, saved.safe_cells_remaining) {
+            return Err(MineSweeperError::InvalidConfig("the counters do not match the states"));
         }
+        mine_sweeper.remember_start();
+        Ok(mine_sweeper)
     }
 
-    fn get_cell_from_position(&self, position: [f64;2]) -> [usize;2] { //TODO: add bounds checking and return a result instead
-        [(position[0] / self.apperance.square_size).trunc() as usize, (position[1] / self.apperance.square_size).trunc() as usize]
+    /// Builds a game from a text board, see `Board::parse` for the mines.
+    /// Digits are revealed cells, `F` is a flagged mine and `f` a wrongly flagged safe cell, everything else is hidden.
+    /// The mines stay where they are written, even on the first reveal
+    pub fn from_text(text: &str) -> Result<Self, MineSweeperError> {
+        let (board, cells) = parse_cells(text)?;
+        let states = cells.iter().map(|row| row.iter().map(|cell| match cell {
+            '0'..='8' => ShownState::Revealed,
+            'F' | 'f' => ShownState::Flagged,
+            _ => ShownState::Hidden,
+        }).collect()).collect();
+        MineSweeper::from_states(board, states)
     }
 
+    /// Starts from `states` instead of with every cell hidden, the mines stay where they are.
+    /// Fails if the states do not match the size of the board
+    pub(crate) fn from_states(board: Board, states: Vec<Vec<ShownState>>) -> Result<Self, MineSweeperError> {
+        if states.len() != board.rows() || states.iter().any(|row| row.len() != board.cols()) {
+            return Err(MineSweeperError::InvalidConfig("the states do not match the size of the board"));
+        }
+        let mut mine_sweeper = MineSweeper::from_board(board);
+        mine_sweeper.first_click = FirstClick::Unsafe;
+        mine_sweeper.first_reveal_done = true;
+        mine_sweeper.states = states;
+        let board = &mine_sweeper.board;
+        if mine_sweeper.states.iter().enumerate().any(|(y, row)| row.iter().enumerate().any(|(x, state)| *state == ShownState::Revealed && board.is_mine([x, y]))) {
+            mine_sweeper.game_state = GameState::Lost;
+        }
+        mine_sweeper.count_shown_cells();
+        mine_sweeper.check_win();
+        mine_sweeper.remember_start();
+        Ok(mine_sweeper)
+    }
 
-    /// Fails if cell is already revealed or flagged, returns true if cell contains a mine
-    fn reveal_cell(&mut self, position: [usize;2]) -> Result<bool, ()> {
-        if self.states[position[1]][position[0]] == ShownState::Hidden {
-            self.states[position[1]][position[0]] = ShownState::Revealed;
-            if let Some(cell) = self.mine_field[position[1]][position[0]] {
-                if cell == 0 {
-                    for neighbour in NEIGHBOURS.iter() {
-                        let x: i32 = position[0] as i32 + neighbour[0] as i32;
-                        if x < 0 || x >= self.states[0].len() as i32 { continue;}
-                        let y: i32 = position[1] as i32 + neighbour[1] as i32;
-                        if y < 0 || y >= self.states.len() as i32 { continue;}
-                        
-                        let _ = self.reveal_cell( [x as usize, y as usize]);
-                    }
-                }
-                return Ok(false)
-            }
-            else{
-                return Ok(true)
-            }
+    /// What the player sees, one line per row with the cells separated by spaces:
+    /// `.` hidden, `F` flagged, `?` marked, a digit for a revealed number and `*` for a revealed mine.
+    /// The mines themselves are written by `board.to_string()`, `from_position` reads both back
+    pub fn to_position_string(&self) -> String {
+        let mut position = String::new();
+        for (y, row) in self.states.iter().enumerate() {
+            let row: Vec<String> = row.iter().enumerate().map(|(x, state)| match (state, self.board.get([x, y])) {
+                (ShownState::Hidden, _) => ".".to_string(),
+                (ShownState::Flagged, _) => "F".to_string(),
+                (ShownState::Marked, _) => "?".to_string(),
+                (ShownState::Revealed, Some(value)) => value.to_string(),
+                (ShownState::Revealed, None) => "*".to_string(),
+            }).collect();
+            position.push_str(&row.join(" "));
+            position.push('\n');
         }
-        return Err(())
+        position
     }
 
-    fn toggle_flag_cell(&mut self, position: [usize;2]) -> Result<(ShownState, bool), ()> { // TODO: refactor to not use matches
-        match self.states[position[1] as usize][position[0] as usize] {
-            ShownState::Hidden => {
-                if let Ok(res) = self.flag_cell(position) {
-                    return Ok((ShownState::Flagged, res))
-                }
+    /// Builds a game from `to_position_string` and the mines in `truth`, see `Board::parse`.
+    /// Fails if the two do not have the same size or a revealed cell does not match the mines.
+    /// The mines stay where they are, even on the first reveal
+    pub fn from_position(position: &str, truth: &str) -> Result<Self, MineSweeperError> {
+        let board = Board::parse(truth)?;
+        let mut mine_sweeper = MineSweeper::from_board(board);
+        mine_sweeper.first_click = FirstClick::Unsafe;
+        mine_sweeper.first_reveal_done = true;
+        let rows: Vec<(usize, &str)> = position.lines().enumerate().map(|(i, line)| (i + 1, line.trim())).filter(|(_, line)| !line.is_empty()).collect();
+        if rows.len() != mine_sweeper.rows() {
+            return Err(MineSweeperError::InvalidConfig("the position and the truth have a different number of rows"));
+        }
+        for (y, (line, row)) in rows.into_iter().enumerate() {
+            let cells: Vec<char> = row.chars().filter(|c| !c.is_whitespace()).collect();
+            if cells.len() != mine_sweeper.cols() {
+                return Err(MineSweeperError::Parse { line, reason: format!("expected {} cells", mine_sweeper.cols()) });
             }
-            ShownState::Flagged => {
-                if let Ok(res) = self.unflag_cell(position) {
-                    return Ok((ShownState::Hidden, res))
+            for (x, cell) in cells.into_iter().enumerate() {
+                let actual = mine_sweeper.board.get([x, y]);
+                mine_sweeper.states[y][x] = match cell {
+                    '.' => ShownState::Hidden,
+                    'F' => ShownState::Flagged,
+                    '?' => ShownState::Marked,
+                    '*' if actual.is_none() => ShownState::Revealed,
+                    '0'..='8' if cell.to_digit(10) == actual.map(u32::from) => ShownState::Revealed,
+                    _ => return Err(MineSweeperError::Parse { line, reason: format!("[{}, {}] is {} but the truth is {:?}", x, y, cell, actual) }),
+                };
+                if cell == '*' {
+                    mine_sweeper.game_state = GameState::Lost;
                 }
             }
-            ShownState::Revealed => {}
         }
-        return Err(());
+        mine_sweeper.count_shown_cells();
+        mine_sweeper.check_win();
+        mine_sweeper.remember_start();
+        Ok(mine_sweeper)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MineSweeperError> {
+        self.saved_game().save(path)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MineSweeperError> {
+        MineSweeper::from_saved_game(SavedGame::load(path)?)
+    }
+
+    fn record(&mut self, action: Action) -> Result<MoveOutcome, MineSweeperError> {
+        let previous_state = self.game_state;
+        if previous_state == GameState::Running {
+            self.move_counts.clicks += 1;
+        }
+        let board_before = if self.first_reveal_done {None} else {Some(self.board.clone())};
+        let outcome = match action {
+            Action::Reveal(position) => self.reveal(position)?,
+            Action::Flag(position) => self.toggle_flag(position)?,
+            Action::Chord(position) => self.chord_around(position)?,
+        };
+        match action {
+            Action::Reveal(_) => self.move_counts.reveals += 1,
+            Action::Flag(_) => self.move_counts.flags += 1,
+            Action::Chord(_) => self.move_counts.chords += 1,
+        }
+        match (outcome.game_state, action) {
+            (GameState::Running, Action::Flag(_)) => {},
+            (GameState::Running, _) => self.clock.start(),
+            _ => self.clock.stop(),
+        }
+        // only the move that did the first reveal can have moved mines
+        let board_before = if self.first_reveal_done && !outcome.revealed.is_empty() {board_before} else {None};
+        self.history.push(HistoryEntry { action, outcome: outcome.clone(), previous_state, board_before });
+        Ok(outcome)
     }
 
-    /// Returns true if successfully unflagged cell and cell contains a mine
-    fn flag_cell(&mut self, position: [usize;2]) -> Result<bool, ()> {
-        if self.states[position[1]][position[0]] == ShownState::Hidden {
-            self.states[position[1]][position[0]] = ShownState::Flagged;
-            return Ok(self.mine_field[position[1]][position[0]] == None)
+    fn reveal(&mut self, cell_pos: [usize;2]) -> Result<MoveOutcome, MineSweeperError> {
+        self.check_playable(cell_pos)?;
+        if !self.first_reveal_done && self.states[cell_pos[1]][cell_pos[0]].is_hidden() {
+            self.first_reveal_done = true;
+            self.board.relocate_mines(cell_pos, self.first_click);
+            self.count_correct_flags();
         }
-        return Err(())
+        let mut revealed: Vec<[usize;2]> = Vec::new();
+        let mut unmarked: Vec<[usize;2]> = Vec::new();
+        let hit_mine = self.reveal_cell(cell_pos, &mut revealed, &mut unmarked)?;
+        Ok(self.finish_reveal(revealed, unmarked, hit_mine))
     }
 
-    /// Returns true if successfully unflagged cell and cell contains a mine
-    fn unflag_cell(&mut self, position: [usize;2]) -> Result<bool, ()> {
-        if self.states[position[1]][position[0]] == ShownState::Flagged {
-            self.states[position[1]][position[0]] = ShownState::Hidden;
-            return Ok(self.mine_field[position[1]][position[0]] == None)
+    fn toggle_flag(&mut self, cell_pos: [usize;2]) -> Result<MoveOutcome, MineSweeperError> {
+        self.check_playable(cell_pos)?;
+        let (previous, state, is_mine) = self.toggle_flag_cell(cell_pos)?;
+        let mut outcome = MoveOutcome {
+            revealed: Vec::new(),
+            flagged: Vec::new(),
+            unflagged: Vec::new(),
+            marked: Vec::new(),
+            unmarked: Vec::new(),
+            hit_mine: false,
+            game_state: self.game_state,
+        };
+        match previous {
+            ShownState::Flagged => {
+                self.flags_placed -= 1;
+                if is_mine {self.mines_flagged -= 1;}
+                outcome.unflagged.push(cell_pos);
+            },
+            ShownState::Marked => outcome.unmarked.push(cell_pos),
+            _ => {},
+        }
+        match state {
+            ShownState::Flagged => {
+                self.flags_placed += 1;
+                if is_mine {self.mines_flagged += 1;}
+                outcome.flagged.push(cell_pos);
+            },
+            ShownState::Marked => outcome.marked.push(cell_pos),
+            _ => {},
         }
-        return Err(())
+        self.check_win();
+        outcome.game_state = self.game_state;
+        Ok(outcome)
     }
-}
 
-fn rand_func(rng: &mut ThreadRng, chance: f64) -> bool {
-    let y = rng.gen_range(0.0, 1.0);
-    return y <= chance
-}
+    fn chord_around(&mut self, cell_pos: [usize;2]) -> Result<MoveOutcome, MineSweeperError> {
+        self.check_playable(cell_pos)?;
+        let mut revealed: Vec<[usize;2]> = Vec::new();
+        let mut unmarked: Vec<[usize;2]> = Vec::new();
+        let hit_mine = self.chord(cell_pos, &mut revealed, &mut unmarked)?;
+        Ok(self.finish_reveal(revealed, unmarked, hit_mine))
+    }
 
-pub fn generate_random_grid(cols: usize, rows: usize, mine_concentration: f64, mine_count_output: &mut usize) -> Vec<Vec<Option<u8>>> {
-    let mut rng_thread = rand::thread_rng();
-    let mut squares: Vec<Vec<Option<u8>>>  = vec![vec![None;cols];rows];
-    for row in squares.iter_mut() {
-        for sq in row.iter_mut() {
-            *sq = if rand_func(&mut rng_thread, mine_concentration) {*mine_count_output += 1; None} else {Some(0)}
+    fn finish_reveal(&mut self, revealed: Vec<[usize;2]>, unmarked: Vec<[usize;2]>, hit_mine: bool) -> MoveOutcome {
+        if hit_mine {
+            self.game_state = GameState::Lost;
+        }
+        self.check_win();
+        MoveOutcome {
+            revealed: revealed.into_iter().map(|cell| (cell, self.board.get(cell))).collect(),
+            flagged: Vec::new(),
+            unflagged: Vec::new(),
+            marked: Vec::new(),
+            unmarked,
+            hit_mine,
+            game_state: self.game_state,
         }
     }
-    // count mines in neighbouring squares
-    count_adjacent_mines(&mut squares);
 
-    return squares;
-}
+    /// Fails if the game is over or `position` is not on the board
+    fn check_playable(&self, position: [usize;2]) -> Result<(), MineSweeperError> {
+        if self.game_state != GameState::Running {
+            return Err(MineSweeperError::GameOver)
+        }
+        if position[0] >= self.cols() || position[1] >= self.rows() {
+            return Err(MineSweeperError::OutOfBounds(position))
+        }
+        Ok(())
+    }
+
+    fn check_win(&mut self) {
+        if self.game_state != GameState::Running {
+            return;
+        }
+        let all_flagged = self.mines_flagged == self.mine_count() && self.flags_placed == self.mine_count();
+        if self.safe_cells_remaining == 0 || (self.win_on_all_flagged && all_flagged) {
+            self.game_state = GameState::Won;
+        }
+    }
 
-fn count_adjacent_mines(squares: &mut Vec<Vec<Option<u8>>>) {
-    for row in 0..squares.len() {
-        for col in 0..squares[row].len() {
-            if squares[row][col].is_some() {
-                for y in 0..3 {
-                    for x in 0..3 {
-                        if x == 1 && y == 1 {continue;} // skip self
-                        let y_sq = (row+y) as isize -1;
-                        let x_sq = (col+x) as isize -1;
-                        if y_sq < 0 || y_sq >= squares.len() as isize {continue;}
-                        if x_sq < 0 || x_sq >= squares[y_sq as usize].len() as isize {continue;}
-                        if squares[row+y-1][col+x-1].is_none() {
-                            match &mut squares[row][col] {
-                                None => {},
-                                Some(sq) => {
-                                    *sq += 1;
-                                }
-                            }
-                        }
+    /// Keeps the states of a game that was not built untouched, its replays have to start from them
+    fn remember_start(&mut self) {
+        if self.first_reveal_done || self.states.iter().flatten().any(|state| *state != ShownState::Hidden) {
+            self.start = Some(self.states.clone());
+        }
+    }
+
+    /// Sets the counters from `states`, for games that did not get there by playing
+    fn count_shown_cells(&mut self) {
+        self.flags_placed = 0;
+        self.safe_cells_remaining = self.cols() * self.rows() - self.mine_count();
+        for (y, row) in self.states.iter().enumerate() {
+            for (x, state) in row.iter().enumerate() {
+                match state {
+                    ShownState::Flagged => self.flags_placed += 1,
+                    ShownState::Revealed if !self.board.is_mine([x, y]) => self.safe_cells_remaining -= 1,
+                    _ => {},
+                }
+            }
+        }
+        self.count_correct_flags();
+    }
+
+    /// Recounts `mines_flagged` after the mine layout has changed
+    fn count_correct_flags(&mut self) {
+        let board = &self.board;
+        self.mines_flagged = self.states.iter().enumerate()
+            .map(|(y, row)| row.iter().enumerate().filter(|(x, state)| **state == ShownState::Flagged && board.is_mine([*x, y])).count())
+            .sum();
+    }
+
+    /// Fails if cell is already revealed or flagged, returns true if cell contains a mine.
+    /// Zeros open up their neighbours breadth first, using `revealed` as the queue, so huge areas cannot overflow the stack.
+    /// Question marks on revealed cells are added to `unmarked`.
+    fn reveal_cell(&mut self, position: [usize;2], revealed: &mut Vec<[usize;2]>, unmarked: &mut Vec<[usize;2]>) -> Result<bool, MineSweeperError> {
+        match self.states[position[1]][position[0]] {
+            ShownState::Hidden => {},
+            ShownState::Marked => unmarked.push(position),
+            ShownState::Revealed => return Err(MineSweeperError::AlreadyRevealed(position)),
+            ShownState::Flagged => return Err(MineSweeperError::Flagged(position)),
+        }
+        self.states[position[1]][position[0]] = ShownState::Revealed;
+        revealed.push(position);
+        if self.board.is_mine(position) {
+            return Ok(true)
+        }
+        self.safe_cells_remaining -= 1;
+
+        let mut next = revealed.len() - 1;
+        while next < revealed.len() {
+            let cell = revealed[next];
+            next += 1;
+            if self.board.get(cell) != Some(0) {
+                continue;
+            }
+            for neighbour in neighbours(cell, self.cols(), self.rows()) {
+                // neighbours of a zero are never mines
+                let state = self.states[neighbour[1]][neighbour[0]];
+                if state.is_hidden() {
+                    if state == ShownState::Marked {
+                        unmarked.push(neighbour);
                     }
+                    self.states[neighbour[1]][neighbour[0]] = ShownState::Revealed;
+                    self.safe_cells_remaining -= 1;
+                    revealed.push(neighbour);
                 }
             }
-            // Print the values as they are being made
-            // if squares[row][col].is_none() {print!("X ")} else {print!("{} ", from_digit(squares[row][col].unwrap().into(), 10).unwrap())};  // add this square´s character to output
-            // if col +1 % squares[row].len() == 0 { // if at the end of row, add new-line character
-            //     println!();
-            // }  
         }
+        Ok(false)
     }
-}
 
-#[allow(dead_code)]
-pub fn print(mine_field: &Vec<Vec<Option<u8>>>) {
-    let mut output: String = "\n".to_string();
-    for row in mine_field.iter() {
-        for (i, sq) in row.iter().enumerate() {
-            output.push(if *sq == None {'X'} else {from_digit(sq.unwrap().into(), 10).unwrap()});  // add this square´s character to output
-            if (i + 1) % row.len() == 0 { // if at the end of row, add new-line character
-                output.push('\n');
-            }  
-            else {  // else add space
-                output.push(' ');
+    /// Fails if cell is not a revealed number with a matching amount of flags around it,
+    /// returns true if any of the revealed cells contains a mine
+    fn chord(&mut self, position: [usize;2], revealed: &mut Vec<[usize;2]>, unmarked: &mut Vec<[usize;2]>) -> Result<bool, MineSweeperError> {
+        if self.states[position[1]][position[0]] != ShownState::Revealed {
+            return Err(MineSweeperError::NotRevealed(position))
+        }
+        // a revealed mine means the game is already lost
+        let value = self.board.get(position).ok_or(MineSweeperError::GameOver)?;
+        let neighbours: Vec<[usize;2]> = self.board.neighbours(position).collect();
+        let flags = neighbours.iter().filter(|n| self.states[n[1]][n[0]] == ShownState::Flagged).count();
+        if flags != value as usize {
+            return Err(MineSweeperError::FlagCountMismatch { position, value, flags })
+        }
+        let mut hit_mine = false;
+        for neighbour in neighbours {
+            if self.states[neighbour[1]][neighbour[0]].is_hidden() && self.reveal_cell(neighbour, revealed, unmarked)? {
+                hit_mine = true;
             }
         }
+        Ok(hit_mine)
+    }
+
+    /// Returns the previous and the new state of the cell and whether it contains a mine
+    fn toggle_flag_cell(&mut self, position: [usize;2]) -> Result<(ShownState, ShownState, bool), MineSweeperError> {
+        let previous = self.states[position[1]][position[0]];
+        self.states[position[1]][position[0]] = match previous {
+            ShownState::Hidden => ShownState::Flagged,
+            ShownState::Flagged if self.question_marks => ShownState::Marked,
+            ShownState::Flagged | ShownState::Marked => ShownState::Hidden,
+            ShownState::Revealed => return Err(MineSweeperError::AlreadyRevealed(position)),
+        };
+        Ok((previous, self.states[position[1]][position[0]], self.board.is_mine(position)))
     }
-    println!("{}", output);
-    //stdout().flush().unwrap();
 }
-/* 
+
 #[cfg(test)]
- mod tests {
+mod tests {
     use super::*;
-    use test::Bencher;
+    use crate::board::BoardMetrics;
+
+    #[test]
+    fn reveal_empty_2000x2000_board() {
+        let mut mine_sweeper = MineSweeper::generate(2000, 2000, Density::Count(0), 0).unwrap();
+        let outcome = mine_sweeper.left_click_cell([1000, 1000]).unwrap();
+        assert_eq!(outcome.revealed.len(), 2000 * 2000);
+        assert_eq!(outcome.revealed[0], ([1000, 1000], Some(0)));
+        assert_eq!(mine_sweeper.safe_cells_remaining(), 0);
+        assert_eq!(outcome.game_state, GameState::Won);
+    }
 
     #[test]
-    fn it_works() {
-        assert_eq!(4, 2*2);
+    fn reveal_around_a_mine() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            . . . .
+            . . . .
+            . . . *
+        ").unwrap();
+        let outcome = mine_sweeper.left_click_cell([0, 0]).unwrap();
+        assert_eq!(outcome.revealed.len(), 11);
+        assert!(outcome.revealed.iter().all(|(_, value)| value.is_some()));
+        assert!(!outcome.hit_mine);
+        assert_eq!(mine_sweeper.game_state, GameState::Won);
     }
 
-    #[bench]
-    fn bench_minefield_creation(b: &mut Bencher) {
-        let mut _mines: usize = 0;
-        b.iter(|| generate_random_grid(100, 100, 0.15, &mut _mines))
+    #[test]
+    fn reveal_stops_at_numbers_and_flags() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            . f . .
+            f f . .
+            . . . .
+        ").unwrap();
+        let outcome = mine_sweeper.left_click_cell([3, 2]).unwrap();
+        assert_eq!(outcome.revealed.len(), 12 - 4);
+        assert_eq!(mine_sweeper.states[0][0], ShownState::Hidden);
+        assert_eq!(mine_sweeper.left_click_cell([3, 2]), Err(MineSweeperError::AlreadyRevealed([3, 2])));
+        assert_eq!(mine_sweeper.left_click_cell([1, 1]), Err(MineSweeperError::Flagged([1, 1])));
+        assert_eq!(mine_sweeper.left_click_cell([4, 0]), Err(MineSweeperError::OutOfBounds([4, 0])));
     }
-} */
\ No newline at end of file
+
+    #[test]
+    fn play_a_board_from_text() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            1 F . .
+            . . . *
+            f . . .
+        ").unwrap();
+        assert_eq!((mine_sweeper.cols(), mine_sweeper.rows(), mine_sweeper.mine_count()), (4, 3, 2));
+        assert_eq!(mine_sweeper.states[0][0], ShownState::Revealed);
+        assert_eq!(mine_sweeper.mines_left(), 0);
+        assert_eq!(mine_sweeper.safe_cells_remaining(), 9);
+        assert_eq!(mine_sweeper.right_click_cell([0, 2]).unwrap().unflagged, vec![[0, 2]]);
+        let outcome = mine_sweeper.chord_cell([0, 0]).unwrap();
+        assert_eq!(outcome.revealed.len(), 2);
+        assert!(matches!(MineSweeper::from_text("3 X\nX ."), Err(MineSweeperError::Parse { line: 1, .. })));
+        assert!(matches!(MineSweeper::from_text("X .\n. . ."), Err(MineSweeperError::Parse { line: 2, .. })));
+    }
+
+    #[test]
+    fn chord_needs_a_revealed_number_with_its_flags() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            1 . .
+            f * .
+        ").unwrap();
+        assert_eq!(mine_sweeper.chord_cell([2, 0]), Err(MineSweeperError::NotRevealed([2, 0])));
+        mine_sweeper.right_click_cell([0, 1]).unwrap();
+        assert_eq!(mine_sweeper.chord_cell([0, 0]), Err(MineSweeperError::FlagCountMismatch { position: [0, 0], value: 1, flags: 0 }));
+        assert_eq!(mine_sweeper.game_state, GameState::Running);
+
+        // the count matches, but the flag is on the wrong cell
+        mine_sweeper.right_click_cell([0, 1]).unwrap();
+        let outcome = mine_sweeper.chord_cell([0, 0]).unwrap();
+        assert!(outcome.hit_mine);
+        assert!(outcome.revealed.contains(&([1, 1], None)));
+        assert_eq!(mine_sweeper.game_state, GameState::Lost);
+    }
+
+    #[test]
+    fn win_by_flagging_every_mine() {
+        for &win_on_all_flagged in [false, true].iter() {
+            let mut mine_sweeper = MineSweeper::from_text("
+                1 *
+                . .
+            ").unwrap();
+            mine_sweeper.win_on_all_flagged = win_on_all_flagged;
+            mine_sweeper.right_click_cell([0, 1]).unwrap();
+            mine_sweeper.right_click_cell([1, 0]).unwrap();
+            // a flag on a safe cell keeps the game going
+            assert_eq!(mine_sweeper.game_state, GameState::Running);
+            mine_sweeper.right_click_cell([0, 1]).unwrap();
+            let expected = if win_on_all_flagged {GameState::Won} else {GameState::Running};
+            assert_eq!(mine_sweeper.game_state, expected);
+        }
+    }
+
+    #[test]
+    fn position_round_trip() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            . . . .
+            . F . .
+            . . . X
+        ").unwrap();
+        mine_sweeper.left_click_cell([3, 0]).unwrap();
+        let mine = [1, 1];
+
+        let position = mine_sweeper.to_position_string();
+        let loaded = MineSweeper::from_position(&position, &mine_sweeper.board.to_string()).unwrap();
+        assert_eq!(loaded.states, mine_sweeper.states);
+        assert_eq!(loaded.board.mine_field, mine_sweeper.board.mine_field);
+        assert_eq!(loaded.safe_cells_remaining(), mine_sweeper.safe_cells_remaining());
+        assert_eq!(loaded.to_position_string(), position);
+
+        mine_sweeper.right_click_cell(mine).unwrap();
+        mine_sweeper.left_click_cell(mine).unwrap();
+        let lost = MineSweeper::from_position(&mine_sweeper.to_position_string(), &mine_sweeper.board.to_string()).unwrap();
+        assert_eq!(lost.game_state, GameState::Lost);
+    }
+
+    #[test]
+    fn question_marks_cycle_and_count_as_hidden() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            . . .
+            . . .
+            . . X
+        ").unwrap();
+        mine_sweeper.question_marks = true;
+        mine_sweeper.right_click_cell([0, 0]).unwrap();
+        let outcome = mine_sweeper.right_click_cell([0, 0]).unwrap();
+        assert_eq!((outcome.unflagged, outcome.marked), (vec![[0, 0]], vec![[0, 0]]));
+        assert_eq!(mine_sweeper.states[0][0], ShownState::Marked);
+        assert_eq!(mine_sweeper.flags_placed(), 0);
+
+        let outcome = mine_sweeper.left_click_cell([1, 1]).unwrap();
+        assert_eq!(outcome.revealed.len(), 1);
+        let outcome = mine_sweeper.left_click_cell([0, 0]).unwrap();
+        assert_eq!(outcome.unmarked, vec![[0, 0]]);
+        assert_eq!(mine_sweeper.game_state, GameState::Won);
+        mine_sweeper.undo().unwrap();
+        assert_eq!(mine_sweeper.states[0][0], ShownState::Marked);
+        assert_eq!(mine_sweeper.to_position_string(), "? . .\n. 1 .\n. . .\n");
+
+        mine_sweeper.right_click_cell([0, 0]).unwrap();
+        assert_eq!(mine_sweeper.states[0][0], ShownState::Hidden);
+    }
+
+    #[test]
+    fn clock_runs_from_the_first_reveal_until_the_game_ends() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            . . .
+            . . .
+            . . X
+        ").unwrap();
+        mine_sweeper.right_click_cell([2, 2]).unwrap();
+        assert!(!mine_sweeper.clock().has_started());
+        assert_eq!(mine_sweeper.left_click_cell([2, 2]), Err(MineSweeperError::Flagged([2, 2])));
+        mine_sweeper.left_click_cell([1, 1]).unwrap();
+        assert!(mine_sweeper.clock().is_running());
+        mine_sweeper.left_click_cell([0, 0]).unwrap();
+        assert_eq!(mine_sweeper.game_state, GameState::Won);
+        assert!(!mine_sweeper.clock().is_running());
+        let elapsed = mine_sweeper.elapsed();
+        assert_eq!(mine_sweeper.elapsed(), elapsed);
+        assert_eq!(mine_sweeper.move_counts(), MoveCounts { clicks: 4, reveals: 2, flags: 1, chords: 0 });
+
+        mine_sweeper.undo().unwrap();
+        assert!(mine_sweeper.clock().is_running());
+    }
+
+    #[test]
+    fn three_bv_and_efficiency() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            . X . .
+            X X . .
+            . . . .
+        ").unwrap();
+        assert_eq!(mine_sweeper.board.metrics(), BoardMetrics { three_bv: 4, openings: 1, isolated_numbers: 3 });
+        mine_sweeper.right_click_cell([1, 0]).unwrap();
+        for cell in [[3, 0], [0, 0], [0, 2]].iter() {
+            mine_sweeper.left_click_cell(*cell).unwrap();
+        }
+        assert_eq!(mine_sweeper.efficiency(), None);
+        mine_sweeper.left_click_cell([1, 2]).unwrap();
+        assert_eq!(mine_sweeper.efficiency(), Some(0.8));
+    }
+
+    #[test]
+    fn undo_and_redo_a_lost_game() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            . . . .
+            . X . .
+            . . . X
+        ").unwrap();
+        mine_sweeper.left_click_cell([3, 0]).unwrap();
+        let states = mine_sweeper.states.clone();
+        let remaining = mine_sweeper.safe_cells_remaining();
+        let mine = [1, 1];
+        mine_sweeper.right_click_cell(mine).unwrap();
+        mine_sweeper.right_click_cell(mine).unwrap();
+        let outcome = mine_sweeper.left_click_cell(mine).unwrap();
+        assert!(outcome.hit_mine);
+        assert_eq!(mine_sweeper.game_state, GameState::Lost);
+
+        assert_eq!(mine_sweeper.undo(), Ok(Action::Reveal(mine)));
+        assert_eq!(mine_sweeper.undo(), Ok(Action::Flag(mine)));
+        assert_eq!(mine_sweeper.flags_placed(), 1);
+        assert_eq!(mine_sweeper.undo(), Ok(Action::Flag(mine)));
+        assert_eq!(mine_sweeper.game_state, GameState::Running);
+        assert_eq!(mine_sweeper.states, states);
+        assert_eq!(mine_sweeper.safe_cells_remaining(), remaining);
+        assert_eq!(mine_sweeper.flags_placed(), 0);
+        assert!(mine_sweeper.undo_used());
+
+        mine_sweeper.redo().unwrap();
+        assert_eq!(mine_sweeper.flags_placed(), 1);
+        mine_sweeper.right_click_cell(mine).unwrap();
+        assert_eq!(mine_sweeper.redo(), Err(MineSweeperError::NothingToRedo));
+    }
+}
@@ -0,0 +1,425 @@
+//! Recording games to a text file and stepping through them again.
+//!
+//! A replay looks like this, times are milliseconds since the recording started:
+//! ```text
+//! mine_sweeper replay 3
+//! size 9 9
+//! seed 42 count 10 safe
+//! win_on_all_flagged false
+//! question_marks false
+//! actions
+//! 0 reveal 4 4
+//! 1520 flag 0 1
+//! 2210 chord 1 1
+//! 3000 undo
+//! ```
+//! Boards that can not be regenerated from a seed store their mines after the first reveal instead of the `seed` line:
+//! ```text
+//! layout
+//! X 1 0
+//! 1 1 0
+//! ```
+//! Games that did not start with every cell hidden, like resumed ones, store their mines and what was shown, with states as in a save:
+//! ```text
+//! position
+//! X 1 0
+//! 1 1 0
+//! states
+//! F R R
+//! H R R
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use crate::board::{write_layout, Board, Density, FirstClick, MineField};
+use crate::error::MineSweeperError;
+use crate::mine_sweeper::{Action, MineSweeper, MoveOutcome, ShownState};
+use crate::text::{density_words, first_click_name, parse_density, parse_error, parse_first_click, parse_layout_row, parse_states_row, parse_word, words, write_states, Lines};
+
+/// Bumped whenever the format changes, older versions are not read
+pub const REPLAY_VERSION: u32 = 3;
+
+/// Where the mines of a replayed game come from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplaySource {
+    /// Regenerated with `Board::generate`, the first reveal moves mines just like in the recorded game
+    Seed { seed: u64, density: Density, first_click: FirstClick },
+    /// The mines as they were after the first reveal
+    Layout(MineField),
+    /// The mines and what was shown before the first recorded step, for games that were loaded or built from a position
+    Position { mine_field: MineField, states: Vec<Vec<ShownState>> },
+}
+
+/// One recorded step of a game
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReplayAction {
+    Act(Action),
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub cols: usize,
+    pub rows: usize,
+    pub source: ReplaySource,
+    pub win_on_all_flagged: bool,
+    pub question_marks: bool,
+    /// Every step with the time since the recording started
+    pub actions: Vec<(Duration, ReplayAction)>,
+}
+
+/// Collects the steps of a game as they are made, see `MineSweeper::start_recording`
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    started: Instant,
+    actions: Vec<(Duration, ReplayAction)>,
+}
+
+impl Recorder {
+    /// `earlier` are the steps that led to the game before the recording started, they are recorded at time zero
+    pub fn new(earlier: Vec<ReplayAction>) -> Self {
+        Recorder {
+            started: Instant::now(),
+            actions: earlier.into_iter().map(|action| (Duration::from_millis(0), action)).collect(),
+        }
+    }
+
+    pub fn push(&mut self, action: ReplayAction) {
+        self.actions.push((self.started.elapsed(), action));
+    }
+
+    pub fn actions(&self) -> &[(Duration, ReplayAction)] {
+        &self.actions
+    }
+}
+
+impl Replay {
+    /// Creates the game as it was before the first recorded step
+    pub fn new_game(&self) -> Result<MineSweeper, MineSweeperError> {
+        let mut mine_sweeper = match &self.source {
+            ReplaySource::Seed { seed, density, first_click } => {
+                let mut mine_sweeper = MineSweeper::generate(self.cols, self.rows, *density, *seed)?;
+                mine_sweeper.first_click = *first_click;
+                mine_sweeper
+            }
+            ReplaySource::Layout(mine_field) => {
+                let mut mine_sweeper = MineSweeper::from_board(Board::from_mine_field(mine_field.clone())?);
+                // the layout already is the one after the first reveal
+                mine_sweeper.first_click = FirstClick::Unsafe;
+                mine_sweeper
+            }
+            ReplaySource::Position { mine_field, states } => MineSweeper::from_states(Board::from_mine_field(mine_field.clone())?, states.clone())?,
+        };
+        mine_sweeper.win_on_all_flagged = self.win_on_all_flagged;
+        mine_sweeper.question_marks = self.question_marks;
+        Ok(mine_sweeper)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MineSweeperError> {
+        Replay::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MineSweeperError> {
+        Ok(fs::write(path, self.to_string())?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, MineSweeperError> {
+        let mut lines = Lines::new(text);
+
+        let (line, header) = lines.next("the header")?;
+        match header.strip_prefix("mine_sweeper replay ") {
+            Some(version) if version.parse::<u32>() == Ok(REPLAY_VERSION) => {},
+            Some(version) => return Err(parse_error(line, format!("unsupported replay version {}", version))),
+            None => return Err(parse_error(line, "not a replay".to_string())),
+        }
+
+        let (line, size) = lines.next("the size")?;
+        let size = words(line, size, "size", 2)?;
+        let cols: usize = parse_word(line, size[0])?;
+        let rows: usize = parse_word(line, size[1])?;
+
+        let (line, source) = lines.next("a seed, layout or position")?;
+        let source = if source == "layout" || source == "position" {
+            let mine_field: MineField = lines.grid(rows, cols, parse_layout_row)?;
+            if source == "layout" {
+                ReplaySource::Layout(mine_field)
+            } else {
+                let (line, states_header) = lines.next("the states")?;
+                if states_header != "states" {
+                    return Err(parse_error(line, "expected states".to_string()));
+                }
+                let states = lines.grid(rows, cols, parse_states_row)?;
+                ReplaySource::Position { mine_field, states }
+            }
+        } else {
+            let seed = words(line, source, "seed", 4)?;
+            let density = parse_density(line, seed[1], seed[2])?;
+            ReplaySource::Seed { seed: parse_word(line, seed[0])?, density, first_click: parse_first_click(line, seed[3])? }
+        };
+
+        let (line, rules) = lines.next("the rules")?;
+        let win_on_all_flagged = parse_word(line, words(line, rules, "win_on_all_flagged", 1)?[0])?;
+
+        let (line, question_marks) = lines.next("whether question marks are on")?;
+        let question_marks = parse_word(line, words(line, question_marks, "question_marks", 1)?[0])?;
+
+        let (line, actions_header) = lines.next("the actions")?;
+        if actions_header != "actions" {
+            return Err(parse_error(line, "expected actions".to_string()));
+        }
+        let mut actions = Vec::new();
+        for (line, action) in lines.rest() {
+            let words: Vec<&str> = action.split_whitespace().collect();
+            let time = Duration::from_millis(parse_word(line, words[0])?);
+            let position = |words: &[&str]| -> Result<[usize;2], MineSweeperError> {
+                if words.len() != 4 {
+                    return Err(parse_error(line, "expected a column and a row".to_string()));
+                }
+                Ok([parse_word(line, words[2])?, parse_word(line, words[3])?])
+            };
+            let action = match words.get(1) {
+                Some(&"reveal") => ReplayAction::Act(Action::Reveal(position(&words)?)),
+                Some(&"flag") => ReplayAction::Act(Action::Flag(position(&words)?)),
+                Some(&"chord") => ReplayAction::Act(Action::Chord(position(&words)?)),
+                Some(&"undo") => ReplayAction::Undo,
+                Some(&"redo") => ReplayAction::Redo,
+                _ => return Err(parse_error(line, format!("unknown action {}", action))),
+            };
+            actions.push((time, action));
+        }
+
+        Ok(Replay { cols, rows, source, win_on_all_flagged, question_marks, actions })
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "mine_sweeper replay {}", REPLAY_VERSION)?;
+        writeln!(f, "size {} {}", self.cols, self.rows)?;
+        match &self.source {
+            ReplaySource::Seed { seed, density, first_click } => {
+                writeln!(f, "seed {} {} {}", seed, density_words(*density), first_click_name(*first_click))?;
+            }
+            ReplaySource::Layout(mine_field) => {
+                writeln!(f, "layout")?;
+                write_layout(f, mine_field)?;
+            }
+            ReplaySource::Position { mine_field, states } => {
+                writeln!(f, "position")?;
+                write_layout(f, mine_field)?;
+                writeln!(f, "states")?;
+                write_states(f, states)?;
+            }
+        }
+        writeln!(f, "win_on_all_flagged {}", self.win_on_all_flagged)?;
+        writeln!(f, "question_marks {}", self.question_marks)?;
+        writeln!(f, "actions")?;
+        for (time, action) in self.actions.iter() {
+            let action = match action {
+                ReplayAction::Act(Action::Reveal(position)) => format!("reveal {} {}", position[0], position[1]),
+                ReplayAction::Act(Action::Flag(position)) => format!("flag {} {}", position[0], position[1]),
+                ReplayAction::Act(Action::Chord(position)) => format!("chord {} {}", position[0], position[1]),
+                ReplayAction::Undo => "undo".to_string(),
+                ReplayAction::Redo => "redo".to_string(),
+            };
+            writeln!(f, "{} {}", time.as_millis(), action)?;
+        }
+        Ok(())
+    }
+}
+
+/// Steps back and forth through a replay
+pub struct ReplayPlayer {
+    replay: Replay,
+    mine_sweeper: MineSweeper,
+    position: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Result<Self, MineSweeperError> {
+        let mine_sweeper = replay.new_game()?;
+        Ok(ReplayPlayer { replay, mine_sweeper, position: 0 })
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// The game after the steps played so far
+    pub fn mine_sweeper(&self) -> &MineSweeper {
+        &self.mine_sweeper
+    }
+
+    /// How many steps have been played
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.replay.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replay.actions.is_empty()
+    }
+
+    /// When the next step was made in the recorded game, `None` at the end of the replay
+    pub fn next_time(&self) -> Option<Duration> {
+        self.replay.actions.get(self.position).map(|(time, _)| *time)
+    }
+
+    /// Plays the next step, `None` at the end of the replay.
+    /// Undo and redo steps return the outcome of an empty move.
+    pub fn step_forward(&mut self) -> Option<Result<MoveOutcome, MineSweeperError>> {
+        let (_, action) = *self.replay.actions.get(self.position)?;
+        self.position += 1;
+        Some(match action {
+            ReplayAction::Act(action) => self.mine_sweeper.act(action),
+            ReplayAction::Undo => self.mine_sweeper.undo().map(|_| self.empty_outcome()),
+            ReplayAction::Redo => self.mine_sweeper.redo(),
+        })
+    }
+
+    /// Takes back the last step, returns false at the start of the replay
+    pub fn step_back(&mut self) -> Result<bool, MineSweeperError> {
+        if self.position == 0 {
+            return Ok(false);
+        }
+        self.seek(self.position - 1)?;
+        Ok(true)
+    }
+
+    /// Jumps to the game after `position` steps, by replaying from the start when going backwards
+    pub fn seek(&mut self, position: usize) -> Result<(), MineSweeperError> {
+        let position = position.min(self.len());
+        if position < self.position {
+            self.mine_sweeper = self.replay.new_game()?;
+            self.position = 0;
+        }
+        while self.position < position {
+            // steps that failed in the recorded game are not recorded, so failing here is not expected
+            if let Some(Err(error)) = self.step_forward() {
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    fn empty_outcome(&self) -> MoveOutcome {
+        MoveOutcome { revealed: Vec::new(), flagged: Vec::new(), unflagged: Vec::new(), marked: Vec::new(), unmarked: Vec::new(), hit_mine: false, game_state: self.mine_sweeper.game_state }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mine_sweeper::ShownState;
+
+    fn play(mine_sweeper: &mut MineSweeper) -> [usize;2] {
+        mine_sweeper.left_click_cell([0, 0]).unwrap();
+        let hidden = (0..mine_sweeper.cols() * mine_sweeper.rows())
+            .map(|i| [i % mine_sweeper.cols(), i / mine_sweeper.cols()])
+            .find(|[x, y]| mine_sweeper.states[*y][*x] == ShownState::Hidden)
+            .unwrap();
+        mine_sweeper.right_click_cell(hidden).unwrap();
+        mine_sweeper.undo().unwrap();
+        mine_sweeper.redo().unwrap();
+        hidden
+    }
+
+    #[test]
+    fn replay_round_trip_from_seed() {
+        let mut mine_sweeper = MineSweeper::generate(9, 9, Density::Count(10), 11).unwrap();
+        mine_sweeper.first_click = FirstClick::Opening;
+        mine_sweeper.start_recording();
+        let flagged = play(&mut mine_sweeper);
+
+        let replay = mine_sweeper.replay().unwrap();
+        assert!(matches!(replay.source, ReplaySource::Seed { seed: 11, .. }));
+        let parsed = Replay::parse(&replay.to_string()).unwrap();
+        assert_eq!(parsed, Replay { actions: replay.actions.iter().map(|(time, action)| (Duration::from_millis(time.as_millis() as u64), *action)).collect(), ..replay });
+
+        let mut player = ReplayPlayer::new(parsed).unwrap();
+        player.seek(player.len()).unwrap();
+        assert_eq!(player.mine_sweeper().states, mine_sweeper.states);
+        assert_eq!(player.mine_sweeper().board(), mine_sweeper.board());
+        player.seek(2).unwrap();
+        player.step_back().unwrap();
+        assert_eq!(player.position(), 1);
+        assert_eq!(player.step_forward().unwrap().unwrap().flagged, vec![flagged]);
+    }
+
+    #[test]
+    fn replay_round_trip_from_layout() {
+        let mut mine_field = vec![vec![Some(0); 9]; 9];
+        mine_field[8][6] = None;
+        mine_field[8][8] = None;
+        let board = Board::from_mine_field(mine_field).unwrap();
+        let mut mine_sweeper = MineSweeper::from_board(board);
+        mine_sweeper.start_recording();
+        play(&mut mine_sweeper);
+
+        let replay = Replay::parse(&mine_sweeper.replay().unwrap().to_string()).unwrap();
+        assert!(matches!(replay.source, ReplaySource::Layout(_)));
+        let mut player = ReplayPlayer::new(replay).unwrap();
+        player.seek(usize::MAX).unwrap();
+        assert_eq!(player.mine_sweeper().states, mine_sweeper.states);
+    }
+
+    #[test]
+    fn replay_round_trip_from_a_resumed_game() {
+        let mut played = MineSweeper::generate(9, 9, Density::Count(10), 11).unwrap();
+        played.first_click = FirstClick::Opening;
+        played.left_click_cell([4, 4]).unwrap();
+        let mut mine_sweeper = MineSweeper::from_saved_game(played.saved_game()).unwrap();
+        mine_sweeper.start_recording();
+        let hidden = |mine: bool| (0..81)
+            .map(|i| [i % 9, i / 9])
+            .find(|[x, y]| mine_sweeper.states[*y][*x] == ShownState::Hidden && mine_sweeper.board().is_mine([*x, *y]) == mine)
+            .unwrap();
+        let (safe, mine) = (hidden(false), hidden(true));
+        mine_sweeper.left_click_cell(safe).unwrap();
+        mine_sweeper.right_click_cell(mine).unwrap();
+
+        let replay = Replay::parse(&mine_sweeper.replay().unwrap().to_string()).unwrap();
+        assert!(matches!(&replay.source, ReplaySource::Position { states, .. } if *states == played.states));
+        let mut player = ReplayPlayer::new(replay).unwrap();
+        assert_eq!(player.mine_sweeper().states, played.states);
+        player.seek(usize::MAX).unwrap();
+        assert_eq!(player.mine_sweeper().states, mine_sweeper.states);
+        assert_eq!(player.mine_sweeper().game_state, mine_sweeper.game_state);
+    }
+
+    #[test]
+    fn redo_moves_undone_before_the_recording() {
+        let mut mine_sweeper = MineSweeper::generate(9, 9, Density::Count(10), 11).unwrap();
+        let flagged = play(&mut mine_sweeper);
+        mine_sweeper.undo().unwrap();
+        mine_sweeper.undo().unwrap();
+        mine_sweeper.start_recording();
+        mine_sweeper.redo().unwrap();
+        mine_sweeper.redo().unwrap();
+
+        let mut player = ReplayPlayer::new(mine_sweeper.replay().unwrap()).unwrap();
+        player.seek(usize::MAX).unwrap();
+        assert_eq!(player.mine_sweeper().states, mine_sweeper.states);
+        assert_eq!(player.mine_sweeper().states[flagged[1]][flagged[0]], ShownState::Flagged);
+    }
+
+    #[test]
+    fn reject_other_versions() {
+        assert!(matches!(Replay::parse("mine_sweeper replay 0\n"), Err(MineSweeperError::Parse { line: 1, .. })));
+    }
+
+    #[test]
+    fn reject_sizes_that_do_not_match_the_layout() {
+        let replay = |size: &str| Replay::parse(&format!("mine_sweeper replay 3\nsize {}\nlayout\nX 1\n1 1\nwin_on_all_flagged false\n", size));
+        assert!(matches!(replay("2 99999999999999999"), Err(MineSweeperError::Parse { line: 6, .. })));
+        assert!(matches!(replay("2 1"), Err(MineSweeperError::Parse { line: 6, .. })));
+        // the layout is fine, the file ends where the question marks should be
+        assert_eq!(replay("2 2").unwrap_err(), MineSweeperError::Parse { line: 6, reason: "expected whether question marks are on but the file ended".to_string() });
+    }
+}
@@ -0,0 +1,248 @@
+//! Saving a game in progress to a text file and resuming it later.
+//!
+//! A save looks like this, `elapsed` is in milliseconds:
+//! ```text
+//! mine_sweeper save 3
+//! size 3 2
+//! seed 42
+//! density count 1
+//! first_click safe
+//! win_on_all_flagged false
+//! question_marks false
+//! first_reveal_done true
+//! game_state running
+//! elapsed 15230
+//! counters 1 1 1
+//! moves 6 4 1 1
+//! layout
+//! X 1 0
+//! 1 1 0
+//! states
+//! F R R
+//! H R R
+//! ```
+//! `seed` and `density` are `none` for boards that were not generated from a seed.
+//! `moves` are the clicks, reveals, flags and chords made so far.
+//! The counters are the flags placed, the flags on mines and the safe cells remaining, they are checked against the states when loading.
+//! States are `H`idden, `R`evealed, `F`lagged or `M`arked with a question mark. Undo history and recordings are not saved.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use crate::board::{write_layout, Density, FirstClick, MineField};
+use crate::error::MineSweeperError;
+use crate::mine_sweeper::{GameState, MoveCounts, ShownState};
+use crate::text::{density_words, first_click_name, parse_density, parse_error, parse_first_click, parse_layout_row, parse_states_row, parse_word, words, write_states, Lines};
+
+/// Bumped whenever the format changes, older versions are not read
+pub const SAVE_VERSION: u32 = 3;
+
+/// Everything needed to resume a game, see `MineSweeper::saved_game` and `MineSweeper::from_saved_game`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedGame {
+    pub cols: usize,
+    pub rows: usize,
+    pub seed: Option<u64>,
+    pub density: Option<Density>,
+    pub first_click: FirstClick,
+    pub win_on_all_flagged: bool,
+    pub question_marks: bool,
+    pub first_reveal_done: bool,
+    pub game_state: GameState,
+    /// How long the game had been played when it was saved
+    pub elapsed: Duration,
+    pub move_counts: MoveCounts,
+    pub flags_placed: usize,
+    pub mines_flagged: usize,
+    pub safe_cells_remaining: usize,
+    /// The mines as they are after the first reveal moved them
+    pub mine_field: MineField,
+    pub states: Vec<Vec<ShownState>>,
+}
+
+impl SavedGame {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MineSweeperError> {
+        SavedGame::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MineSweeperError> {
+        Ok(fs::write(path, self.to_string())?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, MineSweeperError> {
+        let mut lines = Lines::new(text);
+
+        let (line, header) = lines.next("the header")?;
+        match header.strip_prefix("mine_sweeper save ") {
+            Some(version) if version.parse::<u32>() == Ok(SAVE_VERSION) => {},
+            Some(version) => return Err(parse_error(line, format!("unsupported save version {}", version))),
+            None => return Err(parse_error(line, "not a saved game".to_string())),
+        }
+
+        let (line, size) = lines.next("the size")?;
+        let size = words(line, size, "size", 2)?;
+        let cols: usize = parse_word(line, size[0])?;
+        let rows: usize = parse_word(line, size[1])?;
+
+        let (line, seed) = lines.next("the seed")?;
+        let seed = match words(line, seed, "seed", 1)?[0] {
+            "none" => None,
+            seed => Some(parse_word(line, seed)?),
+        };
+
+        let (line, density) = lines.next("the density")?;
+        let density = match density.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["density", "none"] => None,
+            ["density", kind, value] => Some(parse_density(line, kind, value)?),
+            _ => return Err(parse_error(line, "expected density".to_string())),
+        };
+
+        let (line, first_click) = lines.next("the first click")?;
+        let first_click = parse_first_click(line, words(line, first_click, "first_click", 1)?[0])?;
+
+        let (line, rules) = lines.next("the rules")?;
+        let win_on_all_flagged = parse_word(line, words(line, rules, "win_on_all_flagged", 1)?[0])?;
+
+        let (line, question_marks) = lines.next("whether question marks are on")?;
+        let question_marks = parse_word(line, words(line, question_marks, "question_marks", 1)?[0])?;
+
+        let (line, first_reveal_done) = lines.next("whether the first reveal is done")?;
+        let first_reveal_done = parse_word(line, words(line, first_reveal_done, "first_reveal_done", 1)?[0])?;
+
+        let (line, game_state) = lines.next("the game state")?;
+        let game_state = match words(line, game_state, "game_state", 1)?[0] {
+            "running" => GameState::Running,
+            "won" => GameState::Won,
+            "lost" => GameState::Lost,
+            other => return Err(parse_error(line, format!("unknown game state {}", other))),
+        };
+
+        let (line, elapsed) = lines.next("the elapsed time")?;
+        let elapsed = Duration::from_millis(parse_word(line, words(line, elapsed, "elapsed", 1)?[0])?);
+
+        let (line, counters) = lines.next("the counters")?;
+        let counters = words(line, counters, "counters", 3)?;
+        let flags_placed = parse_word(line, counters[0])?;
+        let mines_flagged = parse_word(line, counters[1])?;
+        let safe_cells_remaining = parse_word(line, counters[2])?;
+
+        let (line, moves) = lines.next("the moves")?;
+        let moves = words(line, moves, "moves", 4)?;
+        let move_counts = MoveCounts {
+            clicks: parse_word(line, moves[0])?,
+            reveals: parse_word(line, moves[1])?,
+            flags: parse_word(line, moves[2])?,
+            chords: parse_word(line, moves[3])?,
+        };
+
+        let (line, layout) = lines.next("the layout")?;
+        if layout != "layout" {
+            return Err(parse_error(line, "expected layout".to_string()));
+        }
+        let mine_field: MineField = lines.grid(rows, cols, parse_layout_row)?;
+
+        let (line, states_header) = lines.next("the states")?;
+        if states_header != "states" {
+            return Err(parse_error(line, "expected states".to_string()));
+        }
+        let states = lines.grid(rows, cols, parse_states_row)?;
+
+        Ok(SavedGame {
+            cols,
+            rows,
+            seed,
+            density,
+            first_click,
+            win_on_all_flagged,
+            question_marks,
+            first_reveal_done,
+            game_state,
+            elapsed,
+            move_counts,
+            flags_placed,
+            mines_flagged,
+            safe_cells_remaining,
+            mine_field,
+            states,
+        })
+    }
+}
+
+impl fmt::Display for SavedGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "mine_sweeper save {}", SAVE_VERSION)?;
+        writeln!(f, "size {} {}", self.cols, self.rows)?;
+        match self.seed {
+            Some(seed) => writeln!(f, "seed {}", seed)?,
+            None => writeln!(f, "seed none")?,
+        }
+        match self.density {
+            Some(density) => writeln!(f, "density {}", density_words(density))?,
+            None => writeln!(f, "density none")?,
+        }
+        writeln!(f, "first_click {}", first_click_name(self.first_click))?;
+        writeln!(f, "win_on_all_flagged {}", self.win_on_all_flagged)?;
+        writeln!(f, "question_marks {}", self.question_marks)?;
+        writeln!(f, "first_reveal_done {}", self.first_reveal_done)?;
+        let game_state = match self.game_state {
+            GameState::Running => "running",
+            GameState::Won => "won",
+            GameState::Lost => "lost",
+        };
+        writeln!(f, "game_state {}", game_state)?;
+        writeln!(f, "elapsed {}", self.elapsed.as_millis())?;
+        writeln!(f, "counters {} {} {}", self.flags_placed, self.mines_flagged, self.safe_cells_remaining)?;
+        let moves = self.move_counts;
+        writeln!(f, "moves {} {} {} {}", moves.clicks, moves.reveals, moves.flags, moves.chords)?;
+        writeln!(f, "layout")?;
+        write_layout(f, &self.mine_field)?;
+        writeln!(f, "states")?;
+        write_states(f, &self.states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mine_sweeper::MineSweeper;
+
+    #[test]
+    fn resume_a_saved_game() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            . . . .
+            . F . .
+            . . . X
+        ").unwrap();
+        mine_sweeper.left_click_cell([3, 0]).unwrap();
+
+        let saved = SavedGame::parse(&mine_sweeper.saved_game().to_string()).unwrap();
+        let mut resumed = MineSweeper::from_saved_game(saved).unwrap();
+        assert_eq!(resumed.board(), mine_sweeper.board());
+        assert_eq!(resumed.states, mine_sweeper.states);
+        assert_eq!(resumed.mines_left(), 1);
+        assert_eq!(resumed.safe_cells_remaining(), mine_sweeper.safe_cells_remaining());
+        assert_eq!(resumed.left_click_cell([0, 2]), mine_sweeper.left_click_cell([0, 2]));
+
+        let seeded = MineSweeper::generate(9, 9, Density::Count(10), 5).unwrap();
+        let resumed = MineSweeper::from_saved_game(SavedGame::parse(&seeded.saved_game().to_string()).unwrap()).unwrap();
+        assert_eq!((resumed.seed(), resumed.board()), (Some(5), seeded.board()));
+    }
+
+    #[test]
+    fn reject_counters_that_do_not_match() {
+        let mut saved = MineSweeper::generate(9, 9, Density::Count(10), 5).unwrap().saved_game();
+        saved.flags_placed = 1;
+        assert!(matches!(MineSweeper::from_saved_game(saved), Err(MineSweeperError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn reject_a_size_the_rows_do_not_have() {
+        let saved = MineSweeper::generate(3, 2, Density::Count(1), 5).unwrap().saved_game().to_string();
+        let huge = saved.replace("size 3 2", "size 3 99999999999999999");
+        assert!(matches!(SavedGame::parse(&huge), Err(MineSweeperError::Parse { line: 16, .. })));
+        // without the states the file ends after the layout
+        let cut: String = saved.lines().take(15).map(|line| format!("{}\n", line)).collect();
+        assert_eq!(SavedGame::parse(&cut).unwrap_err(), MineSweeperError::Parse { line: 15, reason: "expected the states but the file ended".to_string() });
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+use rand::prelude::*;
+use crate::error::MineSweeperError;
+use crate::mine_sweeper::Action;
+use crate::solver::{next_queued, unflag, Solver};
+use crate::view::{CellView, PlayerView};
+
+/// Applies the two single cell rules and otherwise guesses the cell next to the lowest number per hidden neighbour
+#[derive(Default)]
+pub struct AlphaAI {
+    action_queue: VecDeque<Action>,
+    starts: Vec<(usize, usize)>,
+    ends: Vec<(usize, usize)>,
+    safes: Vec<Vec<bool>>,
+}
+
+impl Solver for AlphaAI {
+    fn name(&self) -> &'static str {
+        "alpha_ai"
+    }
+
+    fn reset(&mut self, view: &PlayerView) {
+        self.action_queue.clear();
+        self.starts.clear();
+        self.ends.clear();
+        self.safes = vec![vec![false;view.cols()];view.rows()];
+    }
+
+    fn next_action(&mut self, view: &PlayerView) -> Action {
+        // taken out of the solver while searching, the search changes the skips
+        let mut action_queue = std::mem::take(&mut self.action_queue);
+        let action = next_queued(&mut action_queue, view, |view| self.search_field(view), AlphaAI::least_risky); // 6.5% <- bad version // 18%  28% 67%
+        //let action = next_queued(&mut action_queue, view, |view| self.search_field(view), AlphaAI::random_hidden); // 12%  22%
+        self.action_queue = action_queue;
+        action
+    }
+}
+
+impl AlphaAI {
+    fn random_hidden(view: &PlayerView) -> Action {
+        if !(0..view.rows()).any(|y| (0..view.cols()).any(|x| view.get([x, y]).is_hidden())) {
+            return unflag(view);
+        }
+        let mut rng_thread = rand::thread_rng();
+        loop {
+            let x = rng_thread.gen_range(0, view.cols());
+            let y = rng_thread.gen_range(0, view.rows());
+            if view.get([x, y]).is_hidden() {
+                //println!("! revealing random at {:?} !", [x,y]);
+                return Action::Reveal([x, y]);
+            }
+        }
+    }
+
+    /// reveal something that is of low risk
+    /// i.e away from walls, adjacent to a cell with 
+    fn least_risky(view: &PlayerView) -> Action {
+        let risks: Vec<Vec<Option<f32>>> = AlphaAI::calculate_risks(view);
+        // get lowest risk
+        let mut res: Option<(f32,usize,usize)> = None;
+        for (y, row) in risks.iter().enumerate() {
+            for (x, item) in row.iter().enumerate() {
+                if let Some(risk) = item {
+                    if res.is_some() {
+                        if res.unwrap().0 > *risk {
+                            res = Some((*risk, x, y));
+                        }
+                    } else {
+                        res = Some((*risk, x, y));
+                    }
+                }
+            }
+        }
+
+        // reveal
+        if let Some(result) = res {
+            Action::Reveal([result.1, result.2])
+        } else {
+            // could not find any of low risk, revealing random.
+            AlphaAI::random_hidden(view)
+        }
+    }
+
+    fn calculate_risks(view: &PlayerView) -> Vec<Vec<Option<f32>>> {
+        let mut risks: Vec<Vec<Option<f32>>> = vec![vec![None;view.cols()]; view.rows()];
+        let mut y = 0;
+        let mut x = 0;
+        while y < view.rows() {
+            while x < view.cols() {
+                if let Ok((val, flags, hiddens)) = AlphaAI::count_surrounding_possible_mines(view, [x,y]) {
+                    let cell_risk: f32 = (val - flags) as f32 / hiddens.len() as f32;
+                    for hidden in hiddens {
+                        if let Some(cell) = risks[hidden[1]][hidden[0]] {
+                            if cell_risk > cell {
+                                risks[hidden[1]][hidden[0]] = Some(cell_risk);
+                            }
+                        } else {
+                            risks[hidden[1]][hidden[0]] = Some(cell_risk);
+                        }
+                    }
+                }
+                x += 1;
+            }
+            y += 1;
+            x = 0;
+        }
+        risks
+    }
+
+    /// The flags or chord of the first cell that has some, empty if there are none
+    fn search_field(&mut self, view: &PlayerView) -> Vec<Action> {
+        let mut actions = Vec::new();
+        let mut skip: usize = 0;
+        let mut y: usize = 0;
+        let mut x: usize = 0;
+        'outer: while y < view.rows() {
+            while x < view.cols() {
+                if self.try_skip(&mut x, &mut y, &mut skip) { continue 'outer; }
+                if self.eval_cell_for_safe_surrounding_reveals(view, [x, y], &mut actions) {
+                    //println!("found cells at {:?}", [x,y]); 
+                    self.update_skips();
+                    return actions;
+                }
+                x += 1;
+            }
+            y += 1;
+            x = 0;
+        }
+        actions
+    }
+
+    /// Skips start on a safe and ends on an unsafe
+    fn update_skips(&mut self) {
+        let mut skip: usize = 0;
+        let mut y: usize = 0;
+        let mut x: usize = 0;
+        'outer: while y < self.safes.len() {
+            while x < self.safes[0].len() {
+                // if its safe then there should be a skip here
+                if self.safes[y][x] {
+                    // if there are more skips
+                    if skip < self.starts.len() {
+                        // and it starts here
+                        if (x,y) == self.starts[skip] {
+                            // then skip
+                            x = self.ends[skip].0;
+                            y = self.ends[skip].1;
+                            skip += 1;
+                            // and try to update skip
+                            self.update_skip(&mut x, &mut y, &mut skip);
+                            continue 'outer;
+                        }
+                    }
+                    self.create_skip(&mut x, &mut y, &mut skip);
+                    continue 'outer;
+                }
+                x += 1;
+            }
+            y += 1;
+            x = 0;
+        }
+    }
+
+    /// creates a skip at the current indexies
+    fn create_skip(&mut self, x:  &mut usize, y:  &mut usize, skip:  &mut usize) {
+        self.starts.insert(*skip, (*x,*y));
+        if *x+1 < self.safes[0].len() {
+            // inc x
+            *x += 1;
+        } else {
+            // inc y
+            *x = 0;
+            *y += 1;
+        }
+        self.ends.insert(*skip, (*x,*y));
+        *skip += 1;
+        self.update_skip(x, y, skip);
+    }
+
+    /// Only run after having skipped, updates the skip just used.
+    fn update_skip(&mut self, x:  &mut usize, y:  &mut usize, skip:  &mut usize) {
+        'outer: while *y < self.safes.len() {
+            while *x < self.safes[0].len() {
+                if !self.safes[*y][*x] {return;}
+                // skip longer
+                if *x+1 < self.safes[0].len() {
+                    // inc x
+                    self.ends[*skip-1].0 += 1;
+                } else if *y+1 < self.safes.len(){
+                    // inc y
+                    self.ends[*skip-1].1 += 1;
+                    // reset x
+                    self.ends[*skip-1].0 = 0;
+                } else {
+                    return;
+                }
+                // if there are more skips
+                if *skip < self.starts.len() {
+                    // if it starts here
+                    if self.starts[*skip] == self.ends[*skip-1] {
+                        // merge and remove old
+                        self.ends[*skip-1] = self.ends.remove(*skip);
+                        self.starts.remove(*skip);
+                        // jump to next
+                        *x = self.ends[*skip-1].0;
+                        *y = self.ends[*skip-1].1;
+                        continue 'outer;
+                    }
+                }
+                *x += 1;
+            }
+            *y += 1;
+            *x = 0;
+        }
+    }
+
+    fn try_skip(&mut self, x: &mut usize, y: &mut usize, skip: &mut usize) -> bool {
+        if *skip < self.starts.len()
+            && (*x,*y) == self.starts[*skip] {
+                *x = self.ends[*skip].0;
+                *y = self.ends[*skip].1;
+                *skip += 1;
+                return true;
+            }
+        false
+    }
+
+    fn eval_cell_for_safe_surrounding_reveals(&mut self, view: &PlayerView, target: [usize;2], actions: &mut Vec<Action>) -> bool {
+        //println!("searching: {:?}", target);
+        if let Ok((val, flags ,hiddens)) = AlphaAI::count_surrounding_possible_mines(view, target) {
+            if !hiddens.is_empty() {
+                // if val - flags == hidden, flag cells
+                if val - flags == hiddens.len() as u8 {
+                    for position in hiddens.iter() {
+                        actions.push(Action::Flag(*position))
+                    }
+                    self.safes[target[1]][target[0]] = true;
+                    return true;
+                }
+                // if val == flags, reveal cells
+                else if flags == val {
+                    actions.push(Action::Chord(target));
+                    self.safes[target[1]][target[0]] = true;
+                    return true;
+                }
+            } else {
+                self.safes[target[1]][target[0]] = true;
+            }
+        }
+        false
+    }
+
+    /// The number of a revealed cell, the flags around it and the hidden cells around it
+    fn count_surrounding_possible_mines(view: &PlayerView, target: [usize;2]) -> Result<(u8, u8, Vec<[usize;2]>), MineSweeperError>{
+        match view.get(target) {
+            CellView::Revealed(value) => {
+                let mut count: u8 = 0;
+                let mut hidden: Vec<[usize;2]> = Vec::with_capacity(8);
+                for neighbour in view.neighbours(target) {
+                    match view.get(neighbour) {
+                        CellView::Hidden | CellView::Marked => hidden.push(neighbour),
+                        CellView::Flagged => {count += 1;},
+                        _ => {}
+                    }
+                }
+                hidden.shrink_to_fit();
+                Ok((value, count, hidden))
+            }
+            // a revealed mine means the game is lost
+            CellView::Mine => Err(MineSweeperError::GameOver),
+            _ => Err(MineSweeperError::NotRevealed(target)),
+        }
+    }
+}
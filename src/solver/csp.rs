@@ -0,0 +1,188 @@
+//! Treats every revealed number along the frontier as a constraint, "these hidden cells hold this many mines",
+//! and combines overlapping constraints to find safe cells and mines that a single number does not show.
+
+use std::collections::{HashMap, VecDeque};
+use crate::constraint::{deduce_from_count, Constraint};
+use crate::mine_sweeper::Action;
+use crate::solver::{next_queued, proven_moves, unflag, Solver};
+use crate::view::{CellView, PlayerView};
+
+/// Reveals every cell it can prove safe and flags every cell it can prove to be a mine,
+/// using single numbers, subsets (a 1 inside a 2 leaves one mine for the rest of the 2),
+/// differences (a 2 next to a 1 with only one cell of its own puts a mine there, as in 1-2-1) and finally the mine count
+#[derive(Default)]
+pub struct CspSolver {
+    action_queue: VecDeque<Action>,
+}
+
+impl Solver for CspSolver {
+    fn name(&self) -> &'static str {
+        "csp"
+    }
+
+    fn reset(&mut self, _view: &PlayerView) {
+        self.action_queue.clear();
+    }
+
+    fn next_action(&mut self, view: &PlayerView) -> Action {
+        next_queued(&mut self.action_queue, view, |view| proven_moves(deduce(view)), least_risky)
+    }
+}
+
+/// Every constraint from a revealed number that still has hidden cells around it, flags count as mines
+pub(super) fn constraints(view: &PlayerView) -> Vec<Constraint> {
+    let mut constraints = Vec::new();
+    for y in 0..view.rows() {
+        for x in 0..view.cols() {
+            let value = match view.get([x, y]) {
+                CellView::Revealed(value) => value as usize,
+                _ => continue,
+            };
+            let mut cells = Vec::new();
+            let mut flags = 0;
+            for neighbour in view.neighbours([x, y]) {
+                match view.get(neighbour) {
+                    CellView::Hidden | CellView::Marked => cells.push(neighbour),
+                    CellView::Flagged => flags += 1,
+                    _ => {},
+                }
+            }
+            // too many flags means one of them is wrong, nothing can be learned from this number
+            if let Some(constraint) = value.checked_sub(flags).and_then(|mines| Constraint::new(cells, mines)) {
+                constraints.push(constraint);
+            }
+        }
+    }
+    constraints
+}
+
+/// Groups constraints that share cells, directly or through other constraints.
+/// Constraints in different groups can be solved on their own, constraints without cells are left out
+pub(super) fn split_components(constraints: &[Constraint]) -> Vec<Vec<&Constraint>> {
+    let constraints: Vec<&Constraint> = constraints.iter().filter(|constraint| !constraint.cells.is_empty()).collect();
+    let mut index: HashMap<[usize;2], usize> = HashMap::new();
+    for constraint in constraints.iter() {
+        for cell in constraint.cells.iter() {
+            let next = index.len();
+            index.entry(*cell).or_insert(next);
+        }
+    }
+    let mut parent: Vec<usize> = (0..index.len()).collect();
+    for constraint in constraints.iter() {
+        let first = find(&mut parent, index[&constraint.cells[0]]);
+        for cell in constraint.cells.iter().skip(1) {
+            let root = find(&mut parent, index[cell]);
+            parent[root] = first;
+        }
+    }
+    // in the order the constraints come in, so the result does not depend on hashing
+    let mut by_root: HashMap<usize, usize> = HashMap::new();
+    let mut components: Vec<Vec<&Constraint>> = Vec::new();
+    for constraint in constraints.iter() {
+        let root = find(&mut parent, index[&constraint.cells[0]]);
+        let component = *by_root.entry(root).or_insert_with(|| {
+            components.push(Vec::new());
+            components.len() - 1
+        });
+        components[component].push(*constraint);
+    }
+    components
+}
+
+fn find(parent: &mut [usize], cell: usize) -> usize {
+    let mut root = cell;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[cell] = root;
+    root
+}
+
+/// The cells that are proven safe and the cells that are proven mines
+pub(super) fn deduce(view: &PlayerView) -> (Vec<[usize;2]>, Vec<[usize;2]>) {
+    let (safes, mines) = match crate::constraint::deduce(constraints(view)) {
+        (safes, mines) if safes.is_empty() && mines.is_empty() => deduce_from_count(hidden_cells(view), view.mines_left().max(0) as usize),
+        proven => proven,
+    };
+    (safes, mines.into_iter().filter(|cell| view.get(*cell) == CellView::Hidden).collect())
+}
+
+/// The hidden cells, row by row
+pub(super) fn hidden_cells(view: &PlayerView) -> Vec<[usize;2]> {
+    (0..view.rows()).flat_map(|y| (0..view.cols()).map(move |x| [x, y])).filter(|cell| view.get(*cell).is_hidden()).collect()
+}
+
+/// Reveals the hidden cell least likely to be a mine, judging each frontier cell by its most dangerous number
+/// and every other cell by the mines that are left. Ties go to the cell with the fewest neighbours, it is the most likely to open up
+pub(super) fn least_risky(view: &PlayerView) -> Action {
+    let mut risks: HashMap<[usize;2], f64> = HashMap::new();
+    for constraint in constraints(view) {
+        let risk = constraint.mines as f64 / constraint.cells.len() as f64;
+        for cell in constraint.cells {
+            let entry = risks.entry(cell).or_insert(risk);
+            *entry = entry.max(risk);
+        }
+    }
+    let hidden = hidden_cells(view);
+    let interior_risk = view.mines_left().max(0) as f64 / hidden.len() as f64;
+    hidden.into_iter()
+        .map(|cell| (risks.get(&cell).copied().unwrap_or(interior_risk), view.neighbours(cell).count(), cell))
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)))
+        .map_or_else(|| unflag(view), |(_, _, cell)| Action::Reveal(cell))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use crate::board::Density;
+    use crate::mine_sweeper::{GameState, MineSweeper};
+    use crate::solver::play_game;
+
+    #[test]
+    fn solve_one_two_one_and_one_two_two_one() {
+        let mut one_two_one = MineSweeper::from_position("
+            . . .
+            1 2 1
+        ", "
+            X 2 X
+            1 2 1
+        ").unwrap();
+        assert_eq!(deduce(&one_two_one.view()), (vec![], vec![[0, 0], [2, 0]]));
+        play_game(&mut CspSolver::default(), &mut one_two_one);
+        assert_eq!(one_two_one.game_state, GameState::Won);
+
+        let mut one_two_two_one = MineSweeper::from_position("
+            . . . .
+            1 2 2 1
+        ", "
+            1 X X 1
+            1 2 2 1
+        ").unwrap();
+        assert_eq!(deduce(&one_two_two_one.view()), (vec![], vec![[1, 0], [2, 0]]));
+        // the flags leave nothing for the ones
+        one_two_two_one.act(Action::Flag([1, 0])).unwrap();
+        one_two_two_one.act(Action::Flag([2, 0])).unwrap();
+        assert_eq!(deduce(&one_two_two_one.view()), (vec![[0, 0], [3, 0]], vec![]));
+    }
+
+    #[test]
+    fn play_expert_endgames_quickly() {
+        // with few hidden cells left, the mine count used to be combined with every number and one step took seconds
+        for seed in [22, 25].iter() {
+            let mut mine_sweeper = MineSweeper::generate(30, 16, Density::Count(99), *seed).unwrap();
+            let start = Instant::now();
+            play_game(&mut CspSolver::default(), &mut mine_sweeper);
+            assert_ne!(mine_sweeper.game_state, GameState::Running);
+            assert!(start.elapsed() < Duration::from_secs(2), "seed {} took {:?}", seed, start.elapsed());
+        }
+    }
+
+    #[test]
+    fn take_back_a_flag_when_nothing_is_left_to_reveal() {
+        let mut all_flagged = MineSweeper::from_text("F 1 f").unwrap();
+        assert_eq!(CspSolver::default().next_action(&all_flagged.view()), Action::Flag([0, 0]));
+        play_game(&mut CspSolver::default(), &mut all_flagged);
+        assert_ne!(all_flagged.game_state, GameState::Running);
+    }
+}
@@ -0,0 +1,162 @@
+//! AIs that play a game through a `PlayerView`, pick one by name with `from_name` or `from_args`.
+
+mod alpha_ai;
+mod csp;
+mod probability;
+#[cfg(feature = "sat")]
+mod sat;
+
+pub use alpha_ai::AlphaAI;
+pub use csp::CspSolver;
+pub use probability::{mine_probabilities, ProbabilitySolver};
+#[cfg(feature = "sat")]
+pub use sat::SatSolver;
+
+use std::collections::VecDeque;
+use std::env::args;
+use crate::error::MineSweeperError;
+use crate::mine_sweeper::{Action, GameState, MineSweeper, MoveOutcome};
+use crate::view::{CellView, PlayerView};
+
+/// Every name `from_name` knows, the first one is the default
+#[cfg(not(feature = "sat"))]
+pub const SOLVER_NAMES: [&str; 3] = ["alpha_ai", "csp", "probability"];
+#[cfg(feature = "sat")]
+pub const SOLVER_NAMES: [&str; 4] = ["alpha_ai", "csp", "probability", "sat"];
+
+/// Something that can play mine sweeper without seeing the mines
+pub trait Solver {
+    /// The name it is picked by, see `SOLVER_NAMES`
+    fn name(&self) -> &'static str;
+
+    /// Forgets the last game, called before the first move of every game
+    fn reset(&mut self, view: &PlayerView);
+
+    /// The next move, only asked for while the game is running
+    fn next_action(&mut self, view: &PlayerView) -> Action;
+}
+
+pub fn from_name(name: &str) -> Option<Box<dyn Solver>> {
+    match name {
+        "alpha_ai" => Some(Box::new(AlphaAI::default())),
+        "csp" => Some(Box::new(CspSolver::default())),
+        "probability" => Some(Box::new(ProbabilitySolver::default())),
+        #[cfg(feature = "sat")]
+        "sat" => Some(Box::new(SatSolver::default())),
+        _ => None,
+    }
+}
+
+/// The solver after `--solver`, or the first of `SOLVER_NAMES` if not given
+pub fn from_args() -> Result<Box<dyn Solver>, MineSweeperError> {
+    match args().skip_while(|arg| arg != "--solver").nth(1) {
+        Some(name) => from_name(&name).ok_or(MineSweeperError::InvalidConfig("unknown solver")),
+        None if args().any(|arg| arg == "--solver") => Err(MineSweeperError::InvalidConfig("missing solver")),
+        None => Ok(from_name(SOLVER_NAMES[0]).unwrap()),
+    }
+}
+
+/// The first queued move that can still be made. When the queue runs dry it is filled with the moves `deduce` finds,
+/// and if it finds none the move `guess` picks is made
+fn next_queued(queue: &mut VecDeque<Action>, view: &PlayerView, mut deduce: impl FnMut(&PlayerView) -> Vec<Action>, guess: fn(&PlayerView) -> Action) -> Action {
+    loop {
+        match queue.pop_front() {
+            // a queued reveal can already have been done by an earlier opening
+            Some(Action::Reveal(position)) if !view.get(position).is_hidden() => continue,
+            Some(Action::Flag(position)) if view.get(position) != CellView::Hidden => continue,
+            Some(action) => return action,
+            None => {
+                let actions = deduce(view);
+                if actions.is_empty() {
+                    return guess(view);
+                }
+                queue.extend(actions);
+            }
+        }
+    }
+}
+
+/// The guess once no cell is left to reveal: a running game with nothing hidden has a wrong flag, so one is taken back
+fn unflag(view: &PlayerView) -> Action {
+    (0..view.rows())
+        .flat_map(|y| (0..view.cols()).map(move |x| [x, y]))
+        .find(|cell| view.get(*cell) == CellView::Flagged)
+        .map_or(Action::Reveal([0, 0]), Action::Flag)
+}
+
+/// Flags the proven mines, then reveals the proven safe cells
+fn proven_moves((safes, mines): (Vec<[usize;2]>, Vec<[usize;2]>)) -> Vec<Action> {
+    mines.into_iter().map(Action::Flag).chain(safes.into_iter().map(Action::Reveal)).collect()
+}
+
+/// Asks the solver for a move and makes it
+pub fn play_step(solver: &mut dyn Solver, mine_sweeper: &mut MineSweeper) -> Result<MoveOutcome, MineSweeperError> {
+    let action = solver.next_action(&mine_sweeper.view());
+    mine_sweeper.act(action)
+}
+
+/// How many moves in a row may fail or change nothing before `play_game` gives up on a solver
+pub const MAX_IDLE_MOVES: usize = 100;
+
+/// Resets the solver and lets it play until the game is over, returns the number of moves it made.
+/// Stops early, with the game still running, after `MAX_IDLE_MOVES` moves in a row that changed nothing
+pub fn play_game(solver: &mut dyn Solver, mine_sweeper: &mut MineSweeper) -> usize {
+    solver.reset(&mine_sweeper.view());
+    let mut steps = 0;
+    let mut idle = 0;
+    while mine_sweeper.game_state == GameState::Running && idle < MAX_IDLE_MOVES {
+        // a move that fails leaves the game as it was, the solver sees that in the next view
+        match play_step(solver, mine_sweeper) {
+            Ok(outcome) if !outcome.is_empty() => idle = 0,
+            _ => idle += 1,
+        }
+        steps += 1;
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Density;
+
+    #[test]
+    fn every_solver_finishes_a_game() {
+        for name in SOLVER_NAMES.iter() {
+            let mut solver = from_name(name).unwrap();
+            assert_eq!(solver.name(), *name);
+            for seed in 0..20 {
+                let mut mine_sweeper = MineSweeper::generate(16, 16, Density::Count(40), seed).unwrap();
+                assert!(play_game(solver.as_mut(), &mut mine_sweeper) > 0);
+                assert_ne!(mine_sweeper.game_state, GameState::Running);
+            }
+        }
+        assert!(from_name("beta_ai").is_none());
+    }
+
+    /// Keeps revealing the same cell
+    struct Stuck;
+
+    impl Solver for Stuck {
+        fn name(&self) -> &'static str {
+            "stuck"
+        }
+
+        fn reset(&mut self, _view: &PlayerView) {}
+
+        fn next_action(&mut self, _view: &PlayerView) -> Action {
+            Action::Reveal([0, 0])
+        }
+    }
+
+    #[test]
+    fn give_up_on_a_solver_that_changes_nothing() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            . . .
+            . . *
+        ").unwrap();
+        // the first reveal opens [0, 0], every one after that fails
+        assert_eq!(play_game(&mut Stuck, &mut mine_sweeper), MAX_IDLE_MOVES + 1);
+        assert_eq!(mine_sweeper.game_state, GameState::Running);
+    }
+}
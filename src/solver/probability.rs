@@ -0,0 +1,304 @@
+//! The exact chance that each hidden cell is a mine. The frontier, the hidden cells next to a number,
+//! is split into components that share no number, every component's mine layouts are counted by how many mines they use,
+//! and the layouts are weighted by the number of ways the cells away from the frontier can hold the mines that are left.
+
+use std::collections::{HashSet, VecDeque};
+use crate::constraint::Constraint;
+use crate::mine_sweeper::Action;
+use crate::solver::{next_queued, proven_moves, unflag, Solver};
+use crate::solver::csp::{constraints, deduce, least_risky, split_components};
+use crate::view::PlayerView;
+
+/// How many cells the search of one component may try before the probabilities are given up on
+const MAX_SEARCH_NODES: usize = 1_000_000;
+
+/// Makes every move the constraint solver can prove and otherwise reveals the cell least likely to be a mine,
+/// see `mine_probabilities`
+#[derive(Default)]
+pub struct ProbabilitySolver {
+    action_queue: VecDeque<Action>,
+}
+
+impl Solver for ProbabilitySolver {
+    fn name(&self) -> &'static str {
+        "probability"
+    }
+
+    fn reset(&mut self, _view: &PlayerView) {
+        self.action_queue.clear();
+    }
+
+    fn next_action(&mut self, view: &PlayerView) -> Action {
+        next_queued(&mut self.action_queue, view, |view| proven_moves(deduce(view)), guess)
+    }
+}
+
+/// Reveals the cell least likely to be a mine
+fn guess(view: &PlayerView) -> Action {
+    match mine_probabilities(view) {
+        Some(probabilities) => safest(view, &probabilities),
+        // too many layouts to count, fall back to guessing from single numbers
+        None => least_risky(view),
+    }
+}
+
+/// The chance that each hidden cell is a mine, `None` for cells that are not hidden, indexed `[y][x]`.
+/// Flags count as mines. Returns `None` if a component has too many layouts to count
+/// or no layout fits the numbers, which only happens when a flag is wrong
+pub fn mine_probabilities(view: &PlayerView) -> Option<Vec<Vec<Option<f64>>>> {
+    let constraints = constraints(view);
+    let hidden = (0..view.rows()).flat_map(|y| (0..view.cols()).map(move |x| [x, y])).filter(|cell| view.get(*cell).is_hidden()).count();
+    let mines_left = view.mines_left().max(0) as usize;
+
+    let frontier: HashSet<[usize;2]> = constraints.iter().flat_map(|constraint| constraint.cells.iter().copied()).collect();
+    let mut components: Vec<Component> = Vec::new();
+    for constraints in split_components(&constraints) {
+        components.push(Component::count(&constraints, mines_left)?);
+    }
+
+    // prefix[i] and suffix[i] are the layouts of the components before and from i, by mines used
+    let interior = hidden - frontier.len();
+    let mut prefix: Vec<Vec<f64>> = vec![vec![1.0]];
+    for component in components.iter() {
+        let next = convolve(prefix.last().unwrap(), &component.counts, mines_left);
+        prefix.push(next);
+    }
+    let mut suffix: Vec<Vec<f64>> = vec![vec![1.0]];
+    for component in components.iter().rev() {
+        let next = convolve(suffix.last().unwrap(), &component.counts, mines_left);
+        suffix.push(next);
+    }
+    suffix.reverse();
+    let weights = interior_weights(interior, mines_left, prefix.last().unwrap().len() - 1);
+
+    let mut probabilities: Vec<Vec<Option<f64>>> = vec![vec![None; view.cols()]; view.rows()];
+    let all = prefix.last().unwrap();
+    let total: f64 = all.iter().zip(weights.iter()).map(|(count, weight)| count * weight).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    if interior > 0 {
+        let interior_mines: f64 = all.iter().zip(weights.iter()).enumerate()
+            .map(|(frontier_mines, (count, weight))| count * weight * (mines_left - frontier_mines.min(mines_left)) as f64)
+            .sum::<f64>() / total;
+        for (y, row) in probabilities.iter_mut().enumerate() {
+            for (x, probability) in row.iter_mut().enumerate() {
+                if view.get([x, y]).is_hidden() && !frontier.contains(&[x, y]) {
+                    *probability = Some(interior_mines / interior as f64);
+                }
+            }
+        }
+    }
+    for (i, component) in components.iter().enumerate() {
+        // how many ways the rest of the board can be laid out around each mine count of this component
+        let others = convolve(&prefix[i], &suffix[i + 1], mines_left);
+        let rest: Vec<f64> = (0..component.counts.len())
+            .map(|mines| others.iter().enumerate().map(|(other_mines, count)| count * weights.get(mines + other_mines).copied().unwrap_or(0.0)).sum())
+            .collect();
+        let total: f64 = component.counts.iter().zip(rest.iter()).map(|(count, rest)| count * rest).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        for (cell, counts) in component.cells.iter().zip(component.cell_counts.iter()) {
+            let mine: f64 = counts.iter().zip(rest.iter()).map(|(count, rest)| count * rest).sum();
+            probabilities[cell[1]][cell[0]] = Some(mine / total);
+        }
+    }
+    Some(probabilities)
+}
+
+/// Reveals the hidden cell least likely to be a mine, ties go to the cell with the fewest neighbours
+fn safest(view: &PlayerView, probabilities: &[Vec<Option<f64>>]) -> Action {
+    (0..view.rows())
+        .flat_map(|y| (0..view.cols()).map(move |x| [x, y]))
+        .filter_map(|cell| probabilities[cell[1]][cell[0]].map(|probability| (probability, view.neighbours(cell).count(), cell)))
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)))
+        .map_or_else(|| unflag(view), |(_, _, cell)| Action::Reveal(cell))
+}
+
+/// Every layout of `a` next to every layout of `b`, by the mines they use together, up to `max_mines`.
+/// Scaled so the largest count is 1, only the ratios matter and large boards would overflow otherwise
+fn convolve(a: &[f64], b: &[f64], max_mines: usize) -> Vec<f64> {
+    let len = (a.len() + b.len() - 1).min(max_mines + 1);
+    let mut result = vec![0.0; len];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate().take(len.saturating_sub(i)) {
+            result[i + j] += a * b;
+        }
+    }
+    let max = result.iter().cloned().fold(0.0, f64::max);
+    if max > 0.0 {
+        result.iter_mut().for_each(|count| *count /= max);
+    }
+    result
+}
+
+/// For every number of mines on the frontier, in how many ways the interior cells can hold the rest.
+/// Scaled like `convolve`, binomials of large boards do not fit in a float
+fn interior_weights(interior: usize, mines_left: usize, max_frontier_mines: usize) -> Vec<f64> {
+    // ln(interior choose r) for every r, built up one r at a time
+    let mut ln_choose: Vec<f64> = vec![0.0; interior.min(mines_left) + 1];
+    for r in 1..ln_choose.len() {
+        ln_choose[r] = ln_choose[r - 1] + ((interior - r + 1) as f64 / r as f64).ln();
+    }
+    let ln_weights: Vec<Option<f64>> = (0..=max_frontier_mines)
+        .map(|frontier_mines| mines_left.checked_sub(frontier_mines).and_then(|r| ln_choose.get(r).copied()))
+        .collect();
+    let max = ln_weights.iter().flatten().cloned().fold(f64::NEG_INFINITY, f64::max);
+    ln_weights.into_iter().map(|weight| weight.map_or(0.0, |weight| (weight - max).exp())).collect()
+}
+
+/// The layouts of one group of frontier cells, by the mines they use
+struct Component {
+    cells: Vec<[usize;2]>,
+    counts: Vec<f64>,
+    /// For every cell, the layouts in which it is a mine, by the mines they use
+    cell_counts: Vec<Vec<f64>>,
+}
+
+impl Component {
+    /// Counts every layout that fits all of `constraints`, `None` if the search takes too long or nothing fits
+    fn count(constraints: &[&Constraint], mines_left: usize) -> Option<Self> {
+        // cells are searched in the order they are reached through the numbers, so numbers are filled in early
+        let mut cells: Vec<[usize;2]> = Vec::new();
+        let mut queue: VecDeque<[usize;2]> = constraints[0].cells.iter().copied().collect();
+        while let Some(cell) = queue.pop_front() {
+            if cells.contains(&cell) {
+                continue;
+            }
+            cells.push(cell);
+            for constraint in constraints.iter().filter(|constraint| constraint.cells.binary_search(&cell).is_ok()) {
+                queue.extend(constraint.cells.iter().filter(|cell| !cells.contains(cell)));
+            }
+        }
+        let cell_constraints: Vec<Vec<usize>> = cells.iter()
+            .map(|cell| (0..constraints.len()).filter(|c| constraints[*c].cells.binary_search(cell).is_ok()).collect())
+            .collect();
+        let max_mines = cells.len().min(mines_left);
+        let mut search = Search {
+            cell_constraints,
+            targets: constraints.iter().map(|constraint| constraint.mines).collect(),
+            placed: vec![0; constraints.len()],
+            open: constraints.iter().map(|constraint| constraint.cells.len()).collect(),
+            mines: vec![false; cells.len()],
+            max_mines,
+            counts: vec![0.0; max_mines + 1],
+            cell_counts: vec![vec![0.0; max_mines + 1]; cells.len()],
+            nodes: 0,
+        };
+        if !search.search(0, 0) {
+            return None;
+        }
+        // only the ratios matter, this keeps the numbers small
+        let total: f64 = search.counts.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        search.counts.iter_mut().chain(search.cell_counts.iter_mut().flatten()).for_each(|count| *count /= total);
+        Some(Component { cells, counts: search.counts, cell_counts: search.cell_counts })
+    }
+}
+
+/// Backtracking over the cells of a component, one cell is a mine or not at every depth
+struct Search {
+    cell_constraints: Vec<Vec<usize>>,
+    targets: Vec<usize>,
+    /// Mines placed in each constraint so far
+    placed: Vec<usize>,
+    /// Cells of each constraint that are not decided yet
+    open: Vec<usize>,
+    mines: Vec<bool>,
+    max_mines: usize,
+    counts: Vec<f64>,
+    cell_counts: Vec<Vec<f64>>,
+    nodes: usize,
+}
+
+impl Search {
+    /// Returns false if it ran out of nodes
+    fn search(&mut self, depth: usize, mines: usize) -> bool {
+        self.nodes += 1;
+        if self.nodes > MAX_SEARCH_NODES {
+            return false;
+        }
+        if depth == self.mines.len() {
+            self.counts[mines] += 1.0;
+            for (cell, is_mine) in self.mines.iter().enumerate() {
+                if *is_mine {
+                    self.cell_counts[cell][mines] += 1.0;
+                }
+            }
+            return true;
+        }
+        for &is_mine in [false, true].iter() {
+            if is_mine && mines == self.max_mines {
+                continue;
+            }
+            let fits = self.cell_constraints[depth].iter().all(|c| {
+                let placed = self.placed[*c] + is_mine as usize;
+                placed <= self.targets[*c] && placed + self.open[*c] > self.targets[*c]
+            });
+            if !fits {
+                continue;
+            }
+            for c in self.cell_constraints[depth].iter() {
+                self.placed[*c] += is_mine as usize;
+                self.open[*c] -= 1;
+            }
+            self.mines[depth] = is_mine;
+            let finished = self.search(depth + 1, mines + is_mine as usize);
+            for c in self.cell_constraints[depth].iter() {
+                self.placed[*c] -= is_mine as usize;
+                self.open[*c] += 1;
+            }
+            self.mines[depth] = false;
+            if !finished {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mine_sweeper::MineSweeper;
+
+    #[test]
+    fn count_the_interior() {
+        // the 1 has one mine in two cells, the other mine is in one of the three cells to the right
+        let mine_sweeper = MineSweeper::from_position(
+            ". 1 . . . .",
+            "X 1 0 1 X 1",
+        ).unwrap();
+        let probabilities = mine_probabilities(&mine_sweeper.view()).unwrap();
+        let expected = [Some(1.0 / 2.0), None, Some(1.0 / 2.0), Some(1.0 / 3.0), Some(1.0 / 3.0), Some(1.0 / 3.0)];
+        for (probability, expected) in probabilities[0].iter().zip(expected.iter()) {
+            assert!((probability.unwrap_or(-1.0) - expected.unwrap_or(-1.0)).abs() < 1e-9, "{:?}", probabilities);
+        }
+
+        // two numbers without cells in common that hold every mine between them, so the column between them is safe
+        let mine_sweeper = MineSweeper::from_position("
+            . . . . . . .
+            . 2 . . . 1 .
+            . . . . . . .
+        ", "
+            X 2 0 0 0 1 X
+            X 2 0 0 0 1 1
+            1 1 0 0 0 0 0
+        ").unwrap();
+        let probabilities = mine_probabilities(&mine_sweeper.view()).unwrap();
+        assert_eq!((probabilities[0][3], probabilities[1][3]), (Some(0.0), Some(0.0)));
+        assert!((probabilities[0][0].unwrap() - 2.0 / 8.0).abs() < 1e-9);
+        let sum: f64 = probabilities.iter().flatten().flatten().sum();
+        assert!((sum - mine_sweeper.mines_left() as f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn take_back_a_flag_when_nothing_is_left_to_reveal() {
+        let all_flagged = MineSweeper::from_text("F 1 f").unwrap();
+        let probabilities = mine_probabilities(&all_flagged.view()).unwrap();
+        assert_eq!(safest(&all_flagged.view(), &probabilities), Action::Flag([0, 0]));
+    }
+}
@@ -0,0 +1,254 @@
+//! A small DPLL solver with two watched literals per clause, enough for the formulas a mine sweeper frontier gives.
+
+/// A variable or its negation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Lit(usize);
+
+impl Lit {
+    pub fn positive(var: usize) -> Self {
+        Lit(var << 1)
+    }
+
+    pub fn negative(var: usize) -> Self {
+        Lit(var << 1 | 1)
+    }
+
+    pub fn var(self) -> usize {
+        self.0 >> 1
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    pub fn not(self) -> Self {
+        Lit(self.0 ^ 1)
+    }
+}
+
+/// A formula in conjunctive normal form that can be solved many times under different assumptions
+#[derive(Default)]
+pub struct Formula {
+    vars: usize,
+    clauses: Vec<Vec<Lit>>,
+    /// Clauses of a single literal, they are assumed before every solve
+    units: Vec<Lit>,
+    /// An empty clause was added, nothing satisfies the formula
+    empty: bool,
+    /// For every literal the clauses that watch it, a watched literal is one of the first two of its clause
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    trail: Vec<Lit>,
+    /// How much of the trail has been propagated
+    propagated: usize,
+    /// The trail length before each decision, the decision and whether it is already the second try
+    decisions: Vec<(usize, Lit, bool)>,
+}
+
+impl Formula {
+    pub fn new_var(&mut self) -> usize {
+        self.vars += 1;
+        self.watches.push(Vec::new());
+        self.watches.push(Vec::new());
+        self.values.push(None);
+        self.vars - 1
+    }
+
+    pub fn add_clause(&mut self, clause: Vec<Lit>) {
+        match clause.len() {
+            0 => self.empty = true,
+            1 => self.units.push(clause[0]),
+            _ => {
+                self.watches[clause[0].0].push(self.clauses.len());
+                self.watches[clause[1].0].push(self.clauses.len());
+                self.clauses.push(clause);
+            }
+        }
+    }
+
+    /// At most `k` of `lits` are true, with the sequential counter encoding: `counts[i][j]` is true if more than `j` of the first `i + 1` are
+    pub fn at_most(&mut self, lits: &[Lit], k: usize) {
+        if k >= lits.len() {
+            return;
+        }
+        if k == 0 {
+            for lit in lits {
+                self.add_clause(vec![lit.not()]);
+            }
+            return;
+        }
+        let counts: Vec<Vec<usize>> = (0..lits.len() - 1).map(|_| (0..k).map(|_| self.new_var()).collect()).collect();
+        self.add_clause(vec![lits[0].not(), Lit::positive(counts[0][0])]);
+        for count in counts[0].iter().skip(1) {
+            self.add_clause(vec![Lit::negative(*count)]);
+        }
+        for i in 1..lits.len() - 1 {
+            self.add_clause(vec![lits[i].not(), Lit::positive(counts[i][0])]);
+            self.add_clause(vec![Lit::negative(counts[i - 1][0]), Lit::positive(counts[i][0])]);
+            for j in 1..k {
+                self.add_clause(vec![lits[i].not(), Lit::negative(counts[i - 1][j - 1]), Lit::positive(counts[i][j])]);
+                self.add_clause(vec![Lit::negative(counts[i - 1][j]), Lit::positive(counts[i][j])]);
+            }
+            self.add_clause(vec![lits[i].not(), Lit::negative(counts[i - 1][k - 1])]);
+        }
+        self.add_clause(vec![lits[lits.len() - 1].not(), Lit::negative(counts[lits.len() - 2][k - 1])]);
+    }
+
+    /// Exactly `k` of `lits` are true, at least `k` true is at most `len - k` false
+    pub fn exactly(&mut self, lits: &[Lit], k: usize) {
+        if k > lits.len() {
+            self.add_clause(Vec::new());
+            return;
+        }
+        self.at_most(lits, k);
+        let negated: Vec<Lit> = lits.iter().map(|lit| lit.not()).collect();
+        self.at_most(&negated, lits.len() - k);
+    }
+
+    /// The value of `var` in the last model `solve` found
+    pub fn value(&self, var: usize) -> bool {
+        self.values[var] == Some(true)
+    }
+
+    /// Looks for values that satisfy every clause and `assumptions`.
+    /// `None` if it gave up after `max_conflicts` conflicts
+    pub fn solve(&mut self, assumptions: &[Lit], max_conflicts: usize) -> Option<bool> {
+        self.values.iter_mut().for_each(|value| *value = None);
+        self.trail.clear();
+        self.propagated = 0;
+        self.decisions.clear();
+        if self.empty {
+            return Some(false);
+        }
+        let units: Vec<Lit> = self.units.iter().chain(assumptions.iter()).copied().collect();
+        for lit in units {
+            match self.lit_value(lit) {
+                Some(true) => {},
+                Some(false) => return Some(false),
+                None => self.assign(lit),
+            }
+        }
+
+        let mut conflicts = 0;
+        // variables are decided in the order they were made, false first
+        let mut next_var = 0;
+        loop {
+            if !self.propagate() {
+                conflicts += 1;
+                if conflicts > max_conflicts {
+                    return None;
+                }
+                // undo decisions up to the last one that has not been tried both ways, and try the other way
+                loop {
+                    match self.decisions.pop() {
+                        None => return Some(false),
+                        Some((trail_len, lit, second_try)) => {
+                            self.undo(trail_len);
+                            next_var = next_var.min(lit.var());
+                            if !second_try {
+                                self.decisions.push((trail_len, lit.not(), true));
+                                self.assign(lit.not());
+                                break;
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+            while next_var < self.vars && self.values[next_var].is_some() {
+                next_var += 1;
+            }
+            if next_var == self.vars {
+                return Some(true);
+            }
+            let lit = Lit::negative(next_var);
+            self.decisions.push((self.trail.len(), lit, false));
+            self.assign(lit);
+        }
+    }
+
+    fn lit_value(&self, lit: Lit) -> Option<bool> {
+        self.values[lit.var()].map(|value| value != lit.is_negative())
+    }
+
+    fn assign(&mut self, lit: Lit) {
+        self.values[lit.var()] = Some(!lit.is_negative());
+        self.trail.push(lit);
+    }
+
+    fn undo(&mut self, trail_len: usize) {
+        for lit in self.trail.drain(trail_len..) {
+            self.values[lit.var()] = None;
+        }
+        self.propagated = self.propagated.min(trail_len);
+    }
+
+    /// Assigns every literal that is the last one left in its clause, returns false on a conflict
+    fn propagate(&mut self) -> bool {
+        while self.propagated < self.trail.len() {
+            let falsified = self.trail[self.propagated].not();
+            self.propagated += 1;
+            let watching = std::mem::take(&mut self.watches[falsified.0]);
+            let mut kept: Vec<usize> = Vec::with_capacity(watching.len());
+            let mut conflict = false;
+            for (i, &clause) in watching.iter().enumerate() {
+                if conflict {
+                    kept.extend_from_slice(&watching[i..]);
+                    break;
+                }
+                // the falsified literal goes second, the other watched literal first
+                if self.clauses[clause][0] == falsified {
+                    self.clauses[clause].swap(0, 1);
+                }
+                let other = self.clauses[clause][0];
+                if self.lit_value(other) == Some(true) {
+                    kept.push(clause);
+                    continue;
+                }
+                let replacement = (2..self.clauses[clause].len()).find(|j| self.lit_value(self.clauses[clause][*j]) != Some(false));
+                match replacement {
+                    Some(j) => {
+                        self.clauses[clause].swap(1, j);
+                        let lit = self.clauses[clause][1];
+                        self.watches[lit.0].push(clause);
+                    }
+                    None => {
+                        kept.push(clause);
+                        match self.lit_value(other) {
+                            None => self.assign(other),
+                            _ => conflict = true,
+                        }
+                    }
+                }
+            }
+            self.watches[falsified.0] = kept;
+            if conflict {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_exactly() {
+        for n in 1..6 {
+            for k in 0..=n {
+                let mut formula = Formula::default();
+                let vars: Vec<usize> = (0..n).map(|_| formula.new_var()).collect();
+                let lits: Vec<Lit> = vars.iter().map(|var| Lit::positive(*var)).collect();
+                formula.exactly(&lits, k);
+                assert_eq!(formula.solve(&[], 1000), Some(true));
+                assert_eq!(vars.iter().filter(|var| formula.value(**var)).count(), k);
+                // one more than k can not be true
+                let too_many: Vec<Lit> = lits.iter().take(k + 1).copied().collect();
+                let expected = if k == n {Some(true)} else {Some(false)};
+                assert_eq!(formula.solve(&too_many, 1000), expected, "{} of {}", k, n);
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Proves cells safe or mined by handing the numbers to a SAT solver: a cell is safe if no layout
+//! with a mine there satisfies every number. Unlike counting layouts this stays fast on large, dense frontiers.
+
+mod dpll;
+
+use std::collections::{HashMap, VecDeque};
+use crate::constraint::Constraint;
+use crate::mine_sweeper::Action;
+use crate::solver::{next_queued, proven_moves, Solver};
+use crate::solver::csp::{constraints, hidden_cells, least_risky, split_components};
+use crate::view::{CellView, PlayerView};
+use dpll::{Formula, Lit};
+
+/// How long one question to the SAT solver may take before the cell is left undecided
+const MAX_CONFLICTS: usize = 10_000;
+/// The whole board is only used as a constraint near the end, it overlaps every other one
+const MAX_GLOBAL_CELLS: usize = 64;
+
+/// Applies the single number rules, and when they find nothing asks a SAT solver for every frontier cell
+/// whether it can be a mine and whether it can be safe. Guesses like `csp` when nothing is certain
+#[derive(Default)]
+pub struct SatSolver {
+    action_queue: VecDeque<Action>,
+}
+
+impl Solver for SatSolver {
+    fn name(&self) -> &'static str {
+        "sat"
+    }
+
+    fn reset(&mut self, _view: &PlayerView) {
+        self.action_queue.clear();
+    }
+
+    fn next_action(&mut self, view: &PlayerView) -> Action {
+        next_queued(&mut self.action_queue, view, |view| proven_moves(deduce(view)), least_risky)
+    }
+}
+
+/// The cells that are proven safe and the cells that are proven mines
+fn deduce(view: &PlayerView) -> (Vec<[usize;2]>, Vec<[usize;2]>) {
+    let mut constraints = constraints(view);
+    let mut safes: Vec<[usize;2]> = Vec::new();
+    let mut mines: Vec<[usize;2]> = Vec::new();
+    for constraint in constraints.iter() {
+        if constraint.mines == 0 {
+            safes.extend(constraint.cells.iter());
+        } else if constraint.mines == constraint.cells.len() {
+            mines.extend(constraint.cells.iter().filter(|cell| view.get(**cell) == CellView::Hidden));
+        }
+    }
+    if !safes.is_empty() || !mines.is_empty() {
+        return (safes, mines);
+    }
+
+    let hidden = hidden_cells(view);
+    if hidden.len() <= MAX_GLOBAL_CELLS {
+        // the hidden cells hold the mines that are left
+        constraints.extend(Constraint::new(hidden, view.mines_left().max(0) as usize));
+    }
+    for component in split_components(&constraints) {
+        let (component_safes, component_mines) = deduce_component(&component);
+        safes.extend(component_safes);
+        mines.extend(component_mines.into_iter().filter(|cell| view.get(*cell) == CellView::Hidden));
+    }
+    (safes, mines)
+}
+
+/// Finds one layout, then for every cell asks for a layout where it is the other way around.
+/// Every layout found also shows which other cells can go either way, so they are not asked about
+fn deduce_component(constraints: &[&Constraint]) -> (Vec<[usize;2]>, Vec<[usize;2]>) {
+    let mut formula = Formula::default();
+    let mut vars: HashMap<[usize;2], usize> = HashMap::new();
+    let mut cells: Vec<[usize;2]> = Vec::new();
+    for constraint in constraints.iter() {
+        for cell in constraint.cells.iter() {
+            vars.entry(*cell).or_insert_with(|| {
+                cells.push(*cell);
+                formula.new_var()
+            });
+        }
+    }
+    for constraint in constraints.iter() {
+        let lits: Vec<Lit> = constraint.cells.iter().map(|cell| Lit::positive(vars[cell])).collect();
+        formula.exactly(&lits, constraint.mines);
+    }
+
+    // no layout at all means a flag is wrong, nothing can be proven then
+    if formula.solve(&[], MAX_CONFLICTS) != Some(true) {
+        return (Vec::new(), Vec::new());
+    }
+    let first: Vec<bool> = cells.iter().map(|cell| formula.value(vars[cell])).collect();
+    let mut either_way: Vec<bool> = vec![false; cells.len()];
+    let mut safes = Vec::new();
+    let mut mines = Vec::new();
+    for (i, cell) in cells.iter().enumerate() {
+        if either_way[i] {
+            continue;
+        }
+        let var = vars[cell];
+        let flipped = if first[i] {Lit::negative(var)} else {Lit::positive(var)};
+        match formula.solve(&[flipped], MAX_CONFLICTS) {
+            Some(true) => {
+                for (j, other) in cells.iter().enumerate() {
+                    if formula.value(vars[other]) != first[j] {
+                        either_way[j] = true;
+                    }
+                }
+            }
+            Some(false) if first[i] => mines.push(*cell),
+            Some(false) => safes.push(*cell),
+            None => {},
+        }
+    }
+    (safes, mines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mine_sweeper::MineSweeper;
+
+    #[test]
+    fn prove_the_one_two_one() {
+        let mine_sweeper = MineSweeper::from_position("
+            . . .
+            1 2 1
+        ", "
+            X 2 X
+            1 2 1
+        ").unwrap();
+        let (safes, mut mines) = deduce(&mine_sweeper.view());
+        mines.sort_unstable();
+        assert_eq!((safes, mines), (vec![[1, 0]], vec![[0, 0], [2, 0]]));
+    }
+
+    #[test]
+    fn take_back_a_flag_when_nothing_is_left_to_reveal() {
+        let all_flagged = MineSweeper::from_text("F 1 f").unwrap();
+        assert_eq!(deduce(&all_flagged.view()), (vec![], vec![]));
+        assert_eq!(SatSolver::default().next_action(&all_flagged.view()), Action::Flag([0, 0]));
+        // a number without hidden cells around it adds nothing to split
+        assert!(split_components(&[Constraint { cells: vec![], mines: 0 }]).is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+//! Reading and writing the pieces the text formats of replays, saves and configs share.
+
+use std::fmt;
+use crate::board::{Density, FirstClick};
+use crate::error::MineSweeperError;
+use crate::mine_sweeper::ShownState;
+
+/// The lines of a file that are not empty, trimmed and with their line numbers starting at 1
+pub(crate) struct Lines<'a> {
+    lines: Vec<(usize, &'a str)>,
+    next: usize,
+    /// The last line of the file, a line that is missing at the end is reported there
+    end: usize,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let lines = text.lines().enumerate().map(|(i, line)| (i + 1, line.trim())).filter(|(_, line)| !line.is_empty()).collect();
+        Lines { lines, next: 0, end: text.lines().count().max(1) }
+    }
+
+    /// Fails with `expected` if the file has ended
+    pub fn next(&mut self, expected: &str) -> Result<(usize, &'a str), MineSweeperError> {
+        let line = self.lines.get(self.next).copied().ok_or_else(|| parse_error(self.end, format!("expected {} but the file ended", expected)))?;
+        self.next += 1;
+        Ok(line)
+    }
+
+    /// Reads rows with `parse_row` up to the next line that starts with a lowercase keyword,
+    /// failing unless there are as many as the size of the file says
+    pub fn grid<T>(&mut self, rows: usize, cols: usize, parse_row: fn(usize, &str, usize) -> Result<Vec<T>, MineSweeperError>) -> Result<Vec<Vec<T>>, MineSweeperError> {
+        let mut grid = Vec::new();
+        while let Some((line, row)) = self.lines.get(self.next).copied().filter(|(_, row)| !row.starts_with(|c: char| c.is_ascii_lowercase())) {
+            grid.push(parse_row(line, row, cols)?);
+            self.next += 1;
+        }
+        if grid.len() != rows {
+            let line = self.lines.get(self.next).map_or(self.end, |(line, _)| *line);
+            return Err(parse_error(line, format!("the size says {} rows but there are {}", rows, grid.len())));
+        }
+        Ok(grid)
+    }
+
+    /// The lines that have not been read yet
+    pub fn rest(self) -> impl Iterator<Item = (usize, &'a str)> {
+        self.lines.into_iter().skip(self.next)
+    }
+}
+
+pub(crate) fn first_click_name(first_click: FirstClick) -> &'static str {
+    match first_click {
+        FirstClick::Unsafe => "unsafe",
+        FirstClick::Safe => "safe",
+        FirstClick::Opening => "opening",
+        FirstClick::NoGuess => "no_guess",
+    }
+}
+
+pub(crate) fn parse_first_click(line: usize, word: &str) -> Result<FirstClick, MineSweeperError> {
+    match word {
+        "unsafe" => Ok(FirstClick::Unsafe),
+        "safe" => Ok(FirstClick::Safe),
+        "opening" => Ok(FirstClick::Opening),
+        "no_guess" => Ok(FirstClick::NoGuess),
+        other => Err(parse_error(line, format!("unknown first click {}", other))),
+    }
+}
+
+pub(crate) fn density_words(density: Density) -> String {
+    match density {
+        Density::Count(mine_count) => format!("count {}", mine_count),
+        Density::Concentration(concentration) => format!("concentration {}", concentration),
+    }
+}
+
+pub(crate) fn parse_density(line: usize, kind: &str, value: &str) -> Result<Density, MineSweeperError> {
+    match kind {
+        "count" => Ok(Density::Count(parse_word(line, value)?)),
+        "concentration" => Ok(Density::Concentration(parse_word(line, value)?)),
+        other => Err(parse_error(line, format!("unknown density {}", other))),
+    }
+}
+
+/// One line per row, `H`idden, `R`evealed, `F`lagged or `M`arked
+pub(crate) fn write_states(f: &mut fmt::Formatter<'_>, states: &[Vec<ShownState>]) -> fmt::Result {
+    for row in states.iter() {
+        let row: Vec<&str> = row.iter().map(|state| match state {
+            ShownState::Hidden => "H",
+            ShownState::Revealed => "R",
+            ShownState::Flagged => "F",
+            ShownState::Marked => "M",
+        }).collect();
+        writeln!(f, "{}", row.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Reads a row written by `write_states`, failing unless it has `cols` cells
+pub(crate) fn parse_states_row(line: usize, row: &str, cols: usize) -> Result<Vec<ShownState>, MineSweeperError> {
+    let row: Vec<ShownState> = row.split_whitespace().map(|state| match state {
+        "H" => Ok(ShownState::Hidden),
+        "R" => Ok(ShownState::Revealed),
+        "F" => Ok(ShownState::Flagged),
+        "M" => Ok(ShownState::Marked),
+        other => Err(parse_error(line, format!("unknown state {}", other))),
+    }).collect::<Result<_, _>>()?;
+    if row.len() != cols {
+        return Err(parse_error(line, format!("expected {} cells", cols)));
+    }
+    Ok(row)
+}
+
+pub(crate) fn parse_error(line: usize, reason: String) -> MineSweeperError {
+    MineSweeperError::Parse { line, reason }
+}
+
+/// Splits `text` into the words after `key`, failing unless there are exactly `count` of them
+pub(crate) fn words<'a>(line: usize, text: &'a str, key: &str, count: usize) -> Result<Vec<&'a str>, MineSweeperError> {
+    let mut words = text.split_whitespace();
+    if words.next() != Some(key) {
+        return Err(parse_error(line, format!("expected {}", key)));
+    }
+    let words: Vec<&str> = words.collect();
+    if words.len() != count {
+        return Err(parse_error(line, format!("expected {} values after {}", count, key)));
+    }
+    Ok(words)
+}
+
+pub(crate) fn parse_word<T: std::str::FromStr>(line: usize, word: &str) -> Result<T, MineSweeperError> {
+    word.parse().map_err(|_| parse_error(line, format!("could not read {}", word)))
+}
+
+/// Reads a row written by `board::print`, mines are `X` and the numbers are not checked
+pub(crate) fn parse_layout_row(line: usize, row: &str, cols: usize) -> Result<Vec<Option<u8>>, MineSweeperError> {
+    let row: Vec<Option<u8>> = row.split_whitespace().map(|cell| match cell {
+        "X" => Ok(None),
+        value => parse_word(line, value).map(Some),
+    }).collect::<Result<_, _>>()?;
+    if row.len() != cols {
+        return Err(parse_error(line, format!("expected {} cells", cols)));
+    }
+    Ok(row)
+}
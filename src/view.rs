@@ -0,0 +1,117 @@
+use std::fmt;
+use crate::board::neighbours;
+use crate::mine_sweeper::{GameState, MineSweeper, ShownState};
+
+/// One cell as the player sees it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CellView {
+    Hidden,
+    Flagged,
+    /// A question mark, still hidden
+    Marked,
+    Revealed(u8),
+    /// A revealed mine, only seen once the game is lost
+    Mine,
+}
+
+impl CellView {
+    /// Hidden and marked cells can both be revealed
+    pub fn is_hidden(self) -> bool {
+        matches!(self, CellView::Hidden | CellView::Marked)
+    }
+
+    /// The number of a revealed cell
+    pub fn number(self) -> Option<u8> {
+        match self {
+            CellView::Revealed(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A read-only look at a game that only shows what a human player could see, for solvers.
+/// Get one with `MineSweeper::view`
+#[derive(Copy, Clone)]
+pub struct PlayerView<'a> {
+    mine_sweeper: &'a MineSweeper,
+}
+
+impl<'a> PlayerView<'a> {
+    pub(crate) fn new(mine_sweeper: &'a MineSweeper) -> Self {
+        PlayerView { mine_sweeper }
+    }
+
+    pub fn cols(&self) -> usize {
+        self.mine_sweeper.cols()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.mine_sweeper.rows()
+    }
+
+    /// Mines on the whole board, not where they are
+    pub fn mine_count(&self) -> usize {
+        self.mine_sweeper.mine_count()
+    }
+
+    pub fn flags_placed(&self) -> usize {
+        self.mine_sweeper.flags_placed()
+    }
+
+    /// The mine counter, can be negative when there are more flags than mines
+    pub fn mines_left(&self) -> isize {
+        self.mine_sweeper.mines_left()
+    }
+
+    pub fn game_state(&self) -> GameState {
+        self.mine_sweeper.game_state
+    }
+
+    /// Panics if `position` is not on the board
+    pub fn get(&self, position: [usize;2]) -> CellView {
+        match self.mine_sweeper.states[position[1]][position[0]] {
+            ShownState::Hidden => CellView::Hidden,
+            ShownState::Flagged => CellView::Flagged,
+            ShownState::Marked => CellView::Marked,
+            ShownState::Revealed => match self.mine_sweeper.board().get(position) {
+                Some(value) => CellView::Revealed(value),
+                None => CellView::Mine,
+            },
+        }
+    }
+
+    pub fn neighbours(&self, position: [usize;2]) -> impl Iterator<Item = [usize;2]> {
+        neighbours(position, self.cols(), self.rows())
+    }
+}
+
+/// Writes the same as `MineSweeper::to_position_string`
+impl fmt::Display for PlayerView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.mine_sweeper.to_position_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_cells_do_not_show_the_layout() {
+        let mut mine_sweeper = MineSweeper::from_text("
+            1 F . .
+            . . . *
+            . . . .
+        ").unwrap();
+        let view = mine_sweeper.view();
+        assert_eq!((view.get([0, 0]), view.get([1, 0]), view.get([3, 1]), view.get([0, 1])), (CellView::Revealed(1), CellView::Flagged, CellView::Hidden, CellView::Hidden));
+        assert_eq!((view.mine_count(), view.mines_left()), (2, 1));
+        assert_eq!(view.neighbours([0, 0]).count(), 3);
+
+        mine_sweeper.right_click_cell([1, 0]).unwrap();
+        mine_sweeper.left_click_cell([3, 1]).unwrap();
+        let view = mine_sweeper.view();
+        assert_eq!((view.get([1, 0]), view.get([3, 1])), (CellView::Hidden, CellView::Mine));
+        assert_eq!(view.game_state(), GameState::Lost);
+    }
+}